@@ -0,0 +1,44 @@
+use futures::future::BoxFuture;
+use scc::HashMap as ConcurrentHashMap;
+
+use crate::model::id::GuildId;
+
+/// Remembers which node a guild's player was last created on, so it can be preferred again
+///
+/// Implement this to back the affinity with your own storage (e.g. Redis)
+pub trait NodeAffinity: Send + Sync {
+    /// Gets the name of the node a guild last used
+    fn get(&self, guild_id: GuildId) -> BoxFuture<'_, Option<String>>;
+    /// Records the node a guild is now using
+    fn set(&self, guild_id: GuildId, node: String) -> BoxFuture<'_, ()>;
+    /// Forgets the node a guild used
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, ()>;
+}
+
+/// In-memory node affinity, used by default
+#[derive(Default, Debug)]
+pub struct MemoryAffinity {
+    guilds: ConcurrentHashMap<GuildId, String>,
+}
+
+impl NodeAffinity for MemoryAffinity {
+    fn get(&self, guild_id: GuildId) -> BoxFuture<'_, Option<String>> {
+        Box::pin(async move {
+            self.guilds
+                .read_async(&guild_id, |_, node| node.clone())
+                .await
+        })
+    }
+
+    fn set(&self, guild_id: GuildId, node: String) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            self.guilds.upsert_async(guild_id, node).await;
+        })
+    }
+
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            self.guilds.remove_async(&guild_id).await;
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use crate::model::node::LavalinkMessage;
+use crate::node::transport::{HttpRequest, HttpResponse};
+
+/// Receives every interaction Anchorage has with the nodes, without enabling trace logging
+///
+/// Implement this to keep a structured record of the traffic (e.g. for compliance). The methods are called inline
+/// with the requests and messages, so they should hand the data off quickly. Every method does nothing by default
+pub trait AuditSink: Send + Sync {
+    /// Called before a REST request is sent, without the Authorization, User-Agent and node headers
+    fn on_rest_request(&self, request: &HttpRequest) {
+        let _ = request;
+    }
+
+    /// Called once a REST request got a response, whatever its status is
+    fn on_rest_response(&self, request: &HttpRequest, response: &HttpResponse, elapsed: Duration) {
+        let _ = (request, response, elapsed);
+    }
+
+    /// Called for every message a node sent on its websocket
+    fn on_ws_message(&self, node: &str, message: &LavalinkMessage) {
+        let _ = (node, message);
+    }
+}
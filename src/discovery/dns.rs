@@ -0,0 +1,82 @@
+use hickory_resolver::TokioResolver;
+use std::result::Result;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use super::DiscoveredNodes;
+use crate::Anchorage;
+use crate::model::anchorage::NodeOptions;
+use crate::model::error::AnchorageError;
+
+/// Options to discover nodes from a DNS SRV record
+#[derive(Clone)]
+pub struct SrvDiscoveryOptions {
+    /// SRV record to resolve, like `_lavalink._tcp.example.com`
+    pub record: String,
+    /// Options every discovered node uses, the name, host and port are taken from the record
+    pub node: NodeOptions,
+    /// How often the record is resolved again
+    pub interval: Duration,
+}
+
+/// Resolves a SRV record on an interval, keeping the nodes of anchorage in sync with its targets
+pub(crate) fn spawn(
+    anchorage: Anchorage,
+    options: SrvDiscoveryOptions,
+) -> Result<JoinHandle<()>, AnchorageError> {
+    let resolver = TokioResolver::builder_tokio()?.build();
+
+    let handle = tokio::spawn(async move {
+        let mut discovered = DiscoveredNodes::default();
+
+        loop {
+            match resolve(&resolver, &options).await {
+                Ok(nodes) => discovered.sync(&anchorage, nodes).await,
+                Err(error) => {
+                    // keep the current nodes, a failed lookup does not mean they are gone
+                    tracing::warn!(
+                        "Failed to resolve SRV record {} => {:?}",
+                        options.record,
+                        error
+                    );
+                }
+            }
+
+            tokio::select! {
+                _ = sleep(options.interval) => {}
+                _ = anchorage.cancellation_token.cancelled() => break,
+            }
+        }
+
+        tracing::debug!("SRV discovery of {} stopped", options.record);
+    });
+
+    Ok(handle)
+}
+
+/// Resolves the record into the options of each node it points to
+async fn resolve(
+    resolver: &TokioResolver,
+    options: &SrvDiscoveryOptions,
+) -> Result<Vec<NodeOptions>, AnchorageError> {
+    let lookup = resolver.srv_lookup(options.record.as_str()).await?;
+
+    let nodes = lookup
+        .iter()
+        .map(|record| {
+            let host = record.target().to_utf8();
+            let host = host.trim_end_matches('.').to_string();
+            let port = record.port() as u32;
+
+            NodeOptions {
+                name: format!("{}:{}", host, port),
+                host,
+                port,
+                ..options.node.clone()
+            }
+        })
+        .collect();
+
+    Ok(nodes)
+}
@@ -0,0 +1,103 @@
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use tokio::task::JoinHandle;
+
+use super::{DiscoveredNodes, NodeEntry};
+use crate::Anchorage;
+use crate::model::anchorage::NodeOptions;
+use crate::model::error::AnchorageError;
+
+/// Watches a JSON config file, keeping the nodes of anchorage in sync with it
+pub(crate) fn spawn(
+    anchorage: Anchorage,
+    path: PathBuf,
+    template: NodeOptions,
+) -> Result<JoinHandle<()>, AnchorageError> {
+    let (sender, receiver) = flume::unbounded::<notify::Result<Event>>();
+
+    let mut watcher = notify::recommended_watcher(move |event| {
+        sender.send(event).ok();
+    })?;
+
+    // editors usually replace the file instead of writing to it, so watch its directory
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    watcher.watch(directory, RecursiveMode::NonRecursive)?;
+
+    let handle = tokio::spawn(async move {
+        // dropping the watcher stops it, so it lives as long as this task
+        let _watcher = watcher;
+
+        let mut discovered = DiscoveredNodes::default();
+
+        reload(&anchorage, &path, &template, &mut discovered).await;
+
+        loop {
+            let event = tokio::select! {
+                event = receiver.recv_async() => event,
+                _ = anchorage.cancellation_token.cancelled() => break,
+            };
+
+            let Ok(event) = event else {
+                break;
+            };
+
+            let event = match event {
+                Ok(event) => event,
+                Err(error) => {
+                    tracing::warn!("Failed to watch config {:?} => {:?}", path, error);
+                    continue;
+                }
+            };
+
+            let changed = (event.kind.is_create() || event.kind.is_modify())
+                && event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == path.file_name());
+
+            if changed {
+                reload(&anchorage, &path, &template, &mut discovered).await;
+            }
+        }
+
+        tracing::debug!("Config watcher of {:?} stopped", path);
+    });
+
+    Ok(handle)
+}
+
+/// Reads the config again, then syncs the nodes with it
+async fn reload(
+    anchorage: &Anchorage,
+    path: &Path,
+    template: &NodeOptions,
+    discovered: &mut DiscoveredNodes,
+) {
+    match read(path, template).await {
+        Ok(nodes) => {
+            tracing::info!("Config {:?} reloaded with {} node(s)", path, nodes.len());
+            discovered.sync(anchorage, nodes).await;
+        }
+        Err(error) => {
+            // keep the current nodes, the file could be half written
+            tracing::warn!("Failed to reload config {:?} => {:?}", path, error);
+        }
+    }
+}
+
+/// Reads the config into the options of each node in it
+async fn read(path: &Path, template: &NodeOptions) -> Result<Vec<NodeOptions>, AnchorageError> {
+    let bytes = tokio::fs::read(path).await?;
+
+    let nodes = serde_json::from_slice::<Vec<NodeEntry>>(&bytes)?
+        .into_iter()
+        .map(|entry| entry.into_options(template))
+        .collect();
+
+    Ok(nodes)
+}
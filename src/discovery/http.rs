@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::result::Result;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::http::{HeaderMap, Method};
+
+use super::{DiscoveredNodes, NodeEntry};
+use crate::Anchorage;
+use crate::model::anchorage::NodeOptions;
+use crate::model::error::{AnchorageError, LavalinkRestError};
+use crate::node::transport::HttpRequest;
+
+/// Options to discover nodes from a JSON HTTP endpoint
+#[derive(Clone)]
+pub struct HttpDiscoveryOptions {
+    /// Endpoint that returns the node list
+    pub url: String,
+    /// Headers sent when fetching the node list, like an Authorization header
+    pub headers: HashMap<String, String>,
+    /// Options every discovered node uses, the fields returned by the endpoint replace the ones here
+    pub node: NodeOptions,
+    /// How often the node list is fetched again
+    pub interval: Duration,
+}
+
+/// Fetches the node list on an interval, keeping the nodes of anchorage in sync with it
+pub(crate) fn spawn(
+    anchorage: Anchorage,
+    options: HttpDiscoveryOptions,
+) -> Result<JoinHandle<()>, AnchorageError> {
+    let headers = NodeOptions {
+        headers: options.headers.clone(),
+        ..Default::default()
+    }
+    .header_map()?;
+
+    let handle = tokio::spawn(async move {
+        let mut discovered = DiscoveredNodes::default();
+
+        loop {
+            match fetch(&anchorage, &options, &headers).await {
+                Ok(nodes) => discovered.sync(&anchorage, nodes).await,
+                Err(error) => {
+                    // keep the current nodes, a failed fetch does not mean they are gone
+                    tracing::warn!(
+                        "Failed to fetch the node list from {} => {:?}",
+                        options.url,
+                        error
+                    );
+                }
+            }
+
+            tokio::select! {
+                _ = sleep(options.interval) => {}
+                _ = anchorage.cancellation_token.cancelled() => break,
+            }
+        }
+
+        tracing::debug!("HTTP discovery of {} stopped", options.url);
+    });
+
+    Ok(handle)
+}
+
+/// Fetches the node list into the options of each node
+async fn fetch(
+    anchorage: &Anchorage,
+    options: &HttpDiscoveryOptions,
+    headers: &HeaderMap,
+) -> Result<Vec<NodeOptions>, AnchorageError> {
+    let mut request = HttpRequest::new(Method::GET, options.url.as_str());
+
+    request.headers = headers.clone();
+
+    let response = anchorage.transport.execute(request).await?;
+
+    if !response.status.is_success() {
+        return Err(LavalinkRestError::ResponseReceivedNotOk(response.status).into());
+    }
+
+    let nodes = serde_json::from_slice::<Vec<NodeEntry>>(&response.body)?
+        .into_iter()
+        .map(|entry| entry.into_options(&options.node))
+        .collect();
+
+    Ok(nodes)
+}
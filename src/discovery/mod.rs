@@ -0,0 +1,159 @@
+#[cfg(feature = "dns")]
+pub mod dns;
+#[cfg(feature = "watch-config")]
+pub mod file;
+#[cfg(feature = "http-discovery")]
+pub mod http;
+
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+use std::collections::HashSet;
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+use std::time::Duration;
+
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+use crate::Anchorage;
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+use crate::model::anchorage::NodeOptions;
+
+/// How long the players of a node that disappeared get to move or finish before it is removed
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Node listed by a discovery endpoint or a config file
+#[cfg(any(feature = "http-discovery", feature = "watch-config"))]
+#[derive(serde::Deserialize)]
+pub(crate) struct NodeEntry {
+    name: String,
+    host: String,
+    port: u32,
+    auth: String,
+    secure: Option<bool>,
+    groups: Option<Vec<String>>,
+}
+
+#[cfg(any(feature = "http-discovery", feature = "watch-config"))]
+impl NodeEntry {
+    /// Converts this entry to node options, taking the fields it does not have from a template
+    pub(crate) fn into_options(self, template: &NodeOptions) -> NodeOptions {
+        NodeOptions {
+            name: self.name,
+            host: self.host,
+            port: self.port,
+            auth: self.auth,
+            secure: self.secure.unwrap_or(template.secure),
+            groups: self.groups.unwrap_or_else(|| template.groups.clone()),
+            ..template.clone()
+        }
+    }
+}
+
+/// Nodes added by a discovery source, so only those are removed once they disappear from it
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+#[derive(Default)]
+pub(crate) struct DiscoveredNodes {
+    names: HashSet<String>,
+    /// Nodes that disappeared and are being drained, adopted again if they come back before being removed
+    draining: HashSet<String>,
+}
+
+#[cfg(any(feature = "dns", feature = "http-discovery", feature = "watch-config"))]
+impl DiscoveredNodes {
+    /// Adds the nodes that appeared, updates the ones with a new auth, and drains the ones that disappeared
+    /// # Nodes that were not added by this source are left untouched
+    pub(crate) async fn sync(&mut self, anchorage: &Anchorage, nodes: Vec<NodeOptions>) {
+        let found: HashSet<String> = nodes.iter().map(|node| node.name.clone()).collect();
+
+        let mut draining = HashSet::new();
+
+        for name in self.draining.drain() {
+            let exists = anchorage.nodes.read_async(&name, |_, _| ()).await.is_some();
+
+            if exists {
+                draining.insert(name);
+            }
+        }
+
+        self.draining = draining;
+
+        for name in self.names.difference(&found) {
+            tracing::info!("Discovered node {} is gone. Draining it", name);
+
+            let anchorage = anchorage.clone();
+            let name = name.clone();
+
+            self.draining.insert(name.clone());
+
+            // draining waits for the players to move or finish, the next sync shouldn't wait for it
+            tokio::spawn(async move {
+                if let Err(error) = anchorage.drain_node(&name, DRAIN_TIMEOUT).await {
+                    tracing::warn!("Failed to drain discovered node {} => {:?}", name, error);
+                }
+            });
+        }
+
+        let mut names = HashSet::new();
+
+        for options in nodes {
+            let existing = anchorage
+                .nodes
+                .read_async(&options.name, |_, node| node.clone())
+                .await;
+
+            match existing {
+                Some(node)
+                    if self.names.contains(&options.name)
+                        || self.draining.contains(&options.name) =>
+                {
+                    if self.draining.remove(&options.name) {
+                        tracing::info!(
+                            "Discovered node {} came back while draining. Keeping it",
+                            options.name
+                        );
+
+                        // stops the drain before the node gets removed
+                        if let Err(error) = node.set_draining(false).await {
+                            tracing::warn!(
+                                "Failed to stop draining discovered node {} => {:?}",
+                                options.name,
+                                error
+                            );
+                        }
+                    }
+
+                    if node.cached_data().auth != options.auth
+                        && let Err(error) = node.update_auth(&options.auth).await
+                    {
+                        tracing::warn!(
+                            "Failed to update the auth of discovered node {} => {:?}",
+                            options.name,
+                            error
+                        );
+                    }
+
+                    names.insert(options.name);
+                }
+                Some(_) => {
+                    tracing::debug!(
+                        "Discovered node {} is already added by something else. Skipping it",
+                        options.name
+                    );
+                }
+                None => {
+                    let name = options.name.clone();
+
+                    match anchorage.add_node(options).await {
+                        Ok(_) => {
+                            tracing::info!("Discovered node {} was added", name);
+                            names.insert(name);
+                        }
+                        Err(error) => {
+                            tracing::warn!("Failed to add discovered node {} => {:?}", name, error);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.names = names;
+    }
+}
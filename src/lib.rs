@@ -1,39 +1,125 @@
 #![doc = include_str!("../README.md")]
 
+use crate::affinity::{MemoryAffinity, NodeAffinity};
+use crate::audit::AuditSink;
 use crate::model::anchorage::{
-    ConnectionOptions, NodeManagerOptions, NodeOptions, Options, PlayerOptions,
+    ClusterStats, ConnectionOptions, FrozenPlayer, FrozenState, HttpOptions, NodeFailurePolicy,
+    NodeManagerOptions, NodeOptions, Options, OrphanedPlayer, PlayerOptions, RegionOptions,
+    ResumeOptions, ThawedState, VoiceClosePolicy,
 };
+use crate::model::dedup::{DedupStrategy, dedup_tracks};
 use crate::model::error::AnchorageError;
-use crate::model::player::EventType;
-use crate::node::client::Node;
-use crate::player::Player;
-use flume::Receiver;
+use crate::model::gateway::{RoutedVoiceState, VoiceStateUpdate};
+use crate::model::id::{ChannelId, GuildId, UserId};
+use crate::model::node::NodeState;
+use crate::model::player::{
+    DataType, DestroyReason, EventType, LavalinkFilters, LavalinkPlayerOptions, LavalinkVoice,
+    MAX_VOLUME, PlayerSnapshot, Track, UpdatePlayerTrack,
+};
+use crate::node::cache::ResolveCache;
+use crate::node::client::{Node, NodeManagerData, STABLE_AFTER, STATS_HISTORY_SIZE};
+use crate::node::scheduler::RestScheduler;
+#[cfg(feature = "reqwest")]
+use crate::node::transport::ReqwestTransport;
+use crate::node::transport::{HttpTransport, TcpTransport, WsTransport};
+use crate::player::events::{EventChannel, EventReceiver};
+use crate::player::{Player, PlayerLocation, resume_position};
+use crate::preset::Presets;
+use crate::queue::{MemoryQueueStore, Queues};
+use crate::shard::{ShardCount, ShardRouter};
+use futures::future::join_all;
+#[cfg(feature = "reqwest")]
 use reqwest::Client as ReqwestClient;
 use scc::HashMap as ConcurrentHashMap;
-use scc::hash_map::OccupiedEntry;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::result::Result;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::time::{Instant, sleep};
+use tokio_util::sync::CancellationToken;
 
+pub mod affinity;
+pub mod audit;
+pub mod discovery;
 pub mod model;
 pub mod node;
 pub mod player;
+pub mod preset;
+pub mod queue;
+pub mod shard;
+#[cfg(feature = "testcontainers")]
+pub mod testing;
+
+/// Resume timeout (in seconds) freezing enables on nodes that have resuming disabled
+const FREEZE_RESUME_TIMEOUT: u32 = 60;
+
+/// How often a draining node is checked for players that are still on it
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Main entry point of the library that manages the nodes
+/// # Clones share the same nodes, players and queues
+/// # Cloning is cheap, so it can be stored as is in framework state (e.g. serenity's TypeMap or axum's State)
+#[derive(Clone)]
 pub struct Anchorage {
     /// User-Agent Anchorage will use for each request
-    pub user_agent: String,
+    pub user_agent: Arc<str>,
+    /// Client-Name Anchorage will identify as to the nodes, in `Name/Version` format
+    pub client_name: Arc<str>,
     /// Reconnect tries for a node before disconnecting it
     pub reconnect_tries: u16,
-    /// List of nodes connected currently
-    pub nodes: Arc<ConcurrentHashMap<String, Node>>,
-    pub(crate) request: ReqwestClient,
+    /// List of nodes connected currently, see [`Anchorage::nodes_snapshot`] to read them
+    pub(crate) nodes: Arc<ConcurrentHashMap<String, Node>>,
+    /// Storage that remembers which node each guild used
+    pub affinity: Arc<dyn NodeAffinity>,
+    /// How nodes react to voice websocket closes
+    pub voice_close_policy: Arc<VoiceClosePolicy>,
+    /// What happens to the players of a node that gave up reconnecting
+    pub node_failure_policy: NodeFailurePolicy,
+    /// Shuts down every node gracefully once cancelled
+    pub cancellation_token: CancellationToken,
+    /// Queues of every player, shared by all nodes
+    pub queues: Queues,
+    /// Cache of resolve results, shared by all nodes
+    pub resolve_cache: Option<Arc<ResolveCache>>,
+    /// Scheduler of REST requests, shared by all nodes
+    pub rest_scheduler: Option<Arc<RestScheduler>>,
+    /// Highest volume players can be set to, unless overridden per player
+    pub max_volume: u32,
+    /// Filters every new player starts with, unless overridden per player
+    pub default_filters: Option<Arc<LavalinkFilters>>,
+    /// If players reject filters their node has disabled, unless overridden per player
+    pub validate_filters: bool,
+    /// Picks the shard voice state updates are sent on
+    pub shard_router: Arc<dyn ShardRouter>,
+    /// Receives every REST request and websocket message, none if none
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    /// Resuming every node uses unless it sets its own
+    pub resume: Option<ResumeOptions>,
+    /// Regions nodes are grouped in
+    pub regions: Option<Arc<RegionOptions>>,
+    pub(crate) transport: Arc<dyn HttpTransport>,
+    /// If the transport was supplied instead of built by Anchorage
+    custom_transport: bool,
+    pub(crate) ws_transport: Arc<dyn WsTransport>,
+    /// Connection tuning of the request clients built for nodes with TLS options
+    http: HttpOptions,
+    /// Presets players can apply by name, shared by all nodes
+    presets: Presets,
+    /// User-Id the nodes identify with, set once started
+    user_id: Arc<OnceLock<UserId>>,
+    /// Node every player is on, shared with the nodes
+    locations: Arc<ConcurrentHashMap<GuildId, PlayerLocation>>,
+    /// Players of nodes that failed under [`NodeFailurePolicy::KeepOrphans`], mapped by Guild Id
+    orphans: Arc<ConcurrentHashMap<GuildId, OrphanedPlayer>>,
 }
 
 impl Debug for Anchorage {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LavalinkClient")
             .field("user_agent", &self.user_agent)
+            .field("client_name", &self.client_name)
             .field("reconnect_tries", &self.reconnect_tries)
             .field("nodes", &self.nodes.len())
             .finish()
@@ -42,134 +128,846 @@ impl Debug for Anchorage {
 
 impl Anchorage {
     /// Creates a new instance of Anchorage
+    /// # Panics if the request client can't be built, like `reqwest::Client::new` does
+    #[cfg(feature = "reqwest")]
     pub fn new(mut options: Options) -> Self {
+        if let Some(transport) = options.http_transport.take() {
+            return Self::with_transport(options, transport);
+        }
+
+        let client = options.request.take().unwrap_or_else(|| {
+            options
+                .http
+                .unwrap_or_default()
+                .apply(ReqwestClient::builder())
+                .build()
+                .expect("Failed to build the request client")
+        });
+
+        Self::build(options, Arc::new(ReqwestTransport::new(client)), false)
+    }
+
+    /// Creates a new instance of Anchorage that sends REST requests through a transport, the only constructor without the reqwest feature
+    /// # The transport takes precedence over `http_transport` and `request` on the options
+    pub fn with_transport(options: Options, transport: Arc<dyn HttpTransport>) -> Self {
+        Self::build(options, transport, true)
+    }
+
+    fn build(options: Options, transport: Arc<dyn HttpTransport>, custom_transport: bool) -> Self {
+        let http = options.http.unwrap_or_default();
+
         Self {
             user_agent: options
                 .user_agent
-                .unwrap_or(format!("Anchorage/{}", env!("CARGO_PKG_VERSION"))),
+                .unwrap_or(format!("Anchorage/{}", env!("CARGO_PKG_VERSION")))
+                .into(),
+            client_name: options
+                .client_name
+                .unwrap_or(format!("Anchorage/{}", env!("CARGO_PKG_VERSION")))
+                .into(),
             reconnect_tries: options.reconnect_tries.unwrap_or(u16::MAX),
-            request: options
-                .request
-                .get_or_insert_with(ReqwestClient::new)
-                .to_owned(),
+            transport,
+            custom_transport,
+            ws_transport: options
+                .ws_transport
+                .unwrap_or_else(|| Arc::new(TcpTransport)),
+            http,
             nodes: Arc::new(ConcurrentHashMap::new()),
+            affinity: options
+                .affinity
+                .unwrap_or_else(|| Arc::new(MemoryAffinity::default())),
+            voice_close_policy: Arc::new(options.voice_close_policy.unwrap_or_default()),
+            node_failure_policy: options.node_failure_policy.unwrap_or_default(),
+            cancellation_token: options.cancellation_token.unwrap_or_default(),
+            queues: Queues::new(
+                options
+                    .queue_store
+                    .unwrap_or_else(|| Arc::new(MemoryQueueStore::default())),
+                options.autoplay,
+            ),
+            resolve_cache: options
+                .resolve_cache
+                .map(|options| Arc::new(ResolveCache::new(options))),
+            rest_scheduler: options
+                .rest_scheduler
+                .map(|options| Arc::new(RestScheduler::new(options))),
+            max_volume: options.max_volume.unwrap_or(MAX_VOLUME).min(MAX_VOLUME),
+            default_filters: options.default_filters.map(Arc::new),
+            validate_filters: options.validate_filters.unwrap_or(false),
+            presets: Presets::new(options.preset_store),
+            shard_router: options
+                .shard_router
+                .unwrap_or_else(|| Arc::new(ShardCount::default())),
+            audit_sink: options.audit_sink,
+            resume: options.resume,
+            regions: options.regions.map(Arc::new),
+            user_id: Arc::new(OnceLock::new()),
+            locations: Arc::new(ConcurrentHashMap::new()),
+            orphans: Arc::new(ConcurrentHashMap::new()),
         }
     }
 
     /// Creates and connects all the nodes
-    #[tracing::instrument(skip(self, nodes_data))]
+    /// # Returns the result of each node mapped by its name, a node failing does not stop the others
+    #[tracing::instrument(skip(self, user_id, nodes_data))]
     pub async fn start(
         &self,
-        user_id: u64,
-        nodes_data: Vec<impl Into<NodeOptions>>,
-    ) -> Result<(), AnchorageError> {
-        tracing::info!(
-            "Starting Lavalink with user_id ({}) and {} node(s)",
-            user_id,
-            nodes_data.len()
-        );
+        user_id: impl Into<UserId>,
+        nodes_data: impl IntoIterator<Item = impl Into<NodeOptions>>,
+    ) -> Result<HashMap<String, Result<(), AnchorageError>>, AnchorageError> {
+        let user_id = user_id.into();
+        let started_id = *self.user_id.get_or_init(|| user_id);
+
+        if started_id != user_id {
+            return Err(AnchorageError::UserIdMismatch(started_id, user_id));
+        }
+
+        tracing::info!("Starting Lavalink with user_id ({})", user_id);
+
+        if !Self::is_valid_client_name(&self.client_name) {
+            return Err(AnchorageError::InvalidClientName(
+                self.client_name.to_string(),
+            ));
+        }
+
+        let mut results = HashMap::new();
 
         for data in nodes_data {
-            let info = data.into();
-
-            let (node, handle) = Node::new(NodeManagerOptions {
-                name: &info.name,
-                host: &info.host,
-                port: info.port,
-                auth: &info.auth,
-                id: user_id,
-                request: self.request.clone(),
-                user_agent: &self.user_agent,
-                reconnect_tries: self.reconnect_tries,
+            let info: NodeOptions = data.into();
+            let name = info.name.clone();
+
+            let result = self.start_node(user_id, info).await;
+
+            if let Err(error) = &result {
+                tracing::warn!("Lavalink Node {} failed to start => {:?}", name, error);
+            }
+
+            results.insert(name, result);
+        }
+
+        Ok(results)
+    }
+
+    /// Creates and connects a new node while Anchorage is running
+    /// # Nodes with their own User-Id can be added before Anchorage is started
+    pub async fn add_node(&self, options: impl Into<NodeOptions>) -> Result<(), AnchorageError> {
+        let options: NodeOptions = options.into();
+
+        let Some(user_id) = options.user_id.or_else(|| self.user_id.get().copied()) else {
+            return Err(AnchorageError::NotStarted);
+        };
+
+        self.start_node(user_id, options).await
+    }
+
+    /// Disconnects a node, then removes it. Its players receive a destroy event
+    pub async fn remove_node(&self, name: &str) -> Result<(), AnchorageError> {
+        self.disconnect(name, true).await
+    }
+
+    /// Stops picking a node for new players and moves its players to ideal nodes, then removes it
+    /// # Players that can't move stay until they are destroyed or the timeout passes, the ones left then receive a destroy event
+    /// # Turning draining off again with [`Node::set_draining`] stops the drain and keeps the node
+    pub async fn drain_node(&self, name: &str, timeout: Duration) -> Result<(), AnchorageError> {
+        let node = self.node_by_name(name).await?;
+
+        node.set_draining(true).await?;
+
+        let user_id = node.cached_data().id;
+        let mut guilds = vec![];
+
+        node.events_sender
+            .iter_async(|guild_id, _| {
+                guilds.push(*guild_id);
+                true
             })
-            .await?;
+            .await;
 
-            self.nodes.insert_async(info.name, node).await.ok();
+        for guild_id in guilds {
+            if !node.cached_data().draining {
+                return Ok(());
+            }
 
-            let nodes = self.nodes.clone();
+            let target = self
+                .get_ideal_node_filtered(|data| {
+                    data.id == user_id && data.state == NodeState::Connected
+                })
+                .await;
 
-            tokio::spawn(async move {
-                let Ok(name) = handle.await else {
-                    return;
-                };
+            let result = match target {
+                Ok(target) => self.move_player(guild_id, target).await,
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = result {
+                tracing::warn!(
+                    "Player of guild {} stays on draining node {} => {:?}",
+                    guild_id,
+                    name,
+                    error
+                );
+            }
+        }
+
+        let deadline = Instant::now() + timeout;
 
-                let _ = nodes.remove_async(&name).await;
-            });
+        while node.cached_data().draining
+            && !node.events_sender.is_empty()
+            && Instant::now() < deadline
+        {
+            sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        // draining was turned off again (e.g. the node came back), so it stays
+        if !node.cached_data().draining {
+            return Ok(());
         }
 
+        self.remove_node(name).await
+    }
+
+    /// Resolves a DNS SRV record on an interval, adding and removing nodes as its targets change
+    /// # Anchorage needs to be started first. Stops once the cancellation token of Anchorage is cancelled
+    #[cfg(feature = "dns")]
+    pub fn discover_srv(
+        &self,
+        options: discovery::dns::SrvDiscoveryOptions,
+    ) -> Result<tokio::task::JoinHandle<()>, AnchorageError> {
+        discovery::dns::spawn(self.clone(), options)
+    }
+
+    /// Fetches the node list from a JSON HTTP endpoint on an interval, adding and removing nodes as it changes
+    ///
+    /// The endpoint returns an array of nodes with `name`, `host`, `port`, `auth`, and optionally `secure` and `groups`
+    /// # Anchorage needs to be started first. Stops once the cancellation token of Anchorage is cancelled
+    #[cfg(feature = "http-discovery")]
+    pub fn discover_http(
+        &self,
+        options: discovery::http::HttpDiscoveryOptions,
+    ) -> Result<tokio::task::JoinHandle<()>, AnchorageError> {
+        discovery::http::spawn(self.clone(), options)
+    }
+
+    /// Watches a JSON config file, adding, updating and removing nodes every time it changes
+    ///
+    /// The file has the same format as [`Anchorage::discover_http`], the template fills in what the file leaves out
+    /// # Anchorage needs to be started first. Stops once the cancellation token of Anchorage is cancelled
+    #[cfg(feature = "watch-config")]
+    pub fn watch_config(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+        template: NodeOptions,
+    ) -> Result<tokio::task::JoinHandle<()>, AnchorageError> {
+        discovery::file::spawn(self.clone(), path.into(), template)
+    }
+
+    /// Creates and connects a single node
+    async fn start_node(&self, user_id: UserId, info: NodeOptions) -> Result<(), AnchorageError> {
+        if self.nodes.contains_async(&info.name).await {
+            return Err(AnchorageError::NodeAlreadyExists(info.name));
+        }
+
+        let (node, handle) = Node::new(NodeManagerOptions {
+            name: &info.name,
+            websocket_url: info.websocket_url()?,
+            rest_url: info.rest_url()?,
+            headers: info.header_map()?,
+            auth: &info.auth,
+            id: info.user_id.unwrap_or(user_id),
+            transport: self.transport.clone(),
+            custom_transport: self.custom_transport,
+            ws_transport: self.ws_transport.clone(),
+            user_agent: &self.user_agent,
+            client_name: &self.client_name,
+            reconnect_tries: self.reconnect_tries,
+            tls: info.tls.as_ref(),
+            http: &self.http,
+            groups: info.groups.clone(),
+            resume_timeout: info
+                .resume_timeout
+                .or_else(|| self.resume.and_then(|resume| resume.timeout())),
+            stats_history: info.stats_history.unwrap_or(STATS_HISTORY_SIZE),
+            stable_after: info.stable_after.unwrap_or(STABLE_AFTER),
+            min_version: info.min_version.clone(),
+            session_id: info.session_id.clone(),
+            degradation: info.degradation.clone().unwrap_or_default(),
+            quarantine: info.quarantine.clone(),
+            voice_close_policy: VoiceClosePolicy::clone(&self.voice_close_policy),
+            failure_policy: self.node_failure_policy,
+            cancellation_token: self.cancellation_token.child_token(),
+            queues: self.queues.clone(),
+            presets: self.presets.clone(),
+            locations: self.locations.clone(),
+            resolve_cache: self.resolve_cache.clone(),
+            rest_scheduler: self.rest_scheduler.clone(),
+            audit_sink: self.audit_sink.clone(),
+            #[cfg(feature = "debug-recorder")]
+            record_traffic: info.record_traffic.as_deref(),
+        })
+        .await?;
+
+        // another call could have added a node with the same name while this one was connecting
+        if let Err((name, node)) = self.nodes.insert_async(info.name, node).await {
+            node.destroy().await.ok();
+            return Err(AnchorageError::NodeAlreadyExists(name));
+        }
+
+        let anchorage = self.clone();
+
+        tokio::spawn(async move {
+            let Ok((name, failed)) = handle.await else {
+                return;
+            };
+
+            let Some((_, node)) = anchorage.nodes.remove_async(&name).await else {
+                return;
+            };
+
+            if !failed {
+                return;
+            }
+
+            match anchorage.node_failure_policy {
+                NodeFailurePolicy::MigratePlayers => anchorage.migrate_players(node).await,
+                NodeFailurePolicy::KeepOrphans => anchorage.keep_orphans(node).await,
+                NodeFailurePolicy::DestroyPlayers => {}
+            }
+        });
+
         Ok(())
     }
 
+    /// Checks if a Client-Name follows the `Name/Version` format Lavalink recommends
+    fn is_valid_client_name(client_name: &str) -> bool {
+        let Some((name, version)) = client_name.split_once('/') else {
+            return false;
+        };
+
+        !name.is_empty()
+            && !version.is_empty()
+            && !client_name.contains(char::is_whitespace)
+            && !version.contains('/')
+    }
+
     /// Shortcut to get an ideal node with the least amount of load
     pub async fn get_ideal_node(&self) -> Result<Node, AnchorageError> {
+        self.get_ideal_node_filtered(|_| true).await
+    }
+
+    /// Gets an ideal node with the least amount of load, only considering nodes that passes the filter
+    /// # Nodes with equal penalties are picked randomly, to spread the load between them
+    /// # Quarantined nodes are only picked when every node that passes the filter is quarantined. Draining nodes are never picked
+    pub async fn get_ideal_node_filtered(
+        &self,
+        filter: impl Fn(&NodeManagerData) -> bool,
+    ) -> Result<Node, AnchorageError> {
+        let nodes = self.node_list().await;
+
+        let mut best: Option<(bool, f64)> = None;
+        let mut candidates: Vec<Node> = vec![];
+
+        for node in nodes {
+            let data = node.cached_data();
+
+            if data.draining || !filter(&data) {
+                continue;
+            }
+
+            let key = (data.quarantined, data.penalties);
+
+            match best {
+                Some(best) if key > best => continue,
+                Some(best) if key == best => {}
+                _ => {
+                    best = Some(key);
+                    candidates.clear();
+                }
+            }
+
+            candidates.push(node);
+        }
+
+        if candidates.is_empty() {
+            return Err(AnchorageError::NoNodesAvailable);
+        }
+
+        let index = rand::random_range(0..candidates.len());
+
+        Ok(candidates.swap_remove(index))
+    }
+
+    /// Merges the cached statistics of every node into cluster wide totals
+    pub async fn cluster_stats(&self) -> Result<ClusterStats, AnchorageError> {
+        let mut cluster = ClusterStats::default();
+
+        for node in self.node_list().await {
+            let Some(stats) = node.cached_data().statistics else {
+                continue;
+            };
+
+            cluster.nodes += 1;
+            cluster.players += stats.players;
+            cluster.playing_players += stats.playing_players;
+            cluster.memory.free += stats.memory.free;
+            cluster.memory.used += stats.memory.used;
+            cluster.memory.allocated += stats.memory.allocated;
+            cluster.memory.reservable += stats.memory.reservable;
+            cluster.cpu.cores += stats.cpu.cores;
+            cluster.cpu.system_load += stats.cpu.system_load;
+            cluster.cpu.lavalink_load += stats.cpu.lavalink_load;
+        }
+
+        if cluster.nodes > 0 {
+            cluster.cpu.system_load /= cluster.nodes as f64;
+            cluster.cpu.lavalink_load /= cluster.nodes as f64;
+        }
+
+        Ok(cluster)
+    }
+
+    /// Searches on every healthy node at the same time, then merges their results
+    ///
+    /// Tracks that share an ISRC, or a source and identifier, are only kept once (see [`dedup_tracks`]).
+    /// The search only fails if every node failed it
+    pub async fn search(&self, source: &str, query: &str) -> Result<Vec<Track>, AnchorageError> {
+        let identifier = format!("{}:{}", source, query);
+
+        let mut nodes = vec![];
+
+        for node in self.node_list().await {
+            let data = node.cached_data();
+
+            if data.statistics.is_some() && !data.statistics_stale {
+                nodes.push(node);
+            }
+        }
+
+        if nodes.is_empty() {
+            return Err(AnchorageError::NoNodesAvailable);
+        }
+
+        let results = join_all(nodes.iter().map(|node| node.rest.resolve(&identifier))).await;
+
+        let mut tracks: Vec<Track> = vec![];
+        let mut error = None;
+        let mut succeeded = false;
+
+        for result in results {
+            let data = match result {
+                Ok(data) => data,
+                Err(err) => {
+                    error = Some(err);
+                    continue;
+                }
+            };
+
+            succeeded = true;
+
+            let found = match data {
+                DataType::Track(track) => vec![track],
+                DataType::Playlist(playlist) => playlist.tracks,
+                DataType::Search(found) => found,
+                _ => vec![],
+            };
+
+            tracks.extend(found);
+        }
+
+        match error {
+            Some(error) if !succeeded => Err(error.into()),
+            _ => Ok(dedup_tracks(tracks, DedupStrategy::Isrc)),
+        }
+    }
+
+    /// Clones every node in cache into a list
+    async fn node_list(&self) -> Vec<Node> {
         let mut nodes = vec![];
 
         self.nodes
             .iter_async(|_, node| {
                 nodes.push(node.clone());
-                false
+                true
             })
             .await;
 
-        let mut penalties: f64 = 0.0;
-        let mut selected_node: Option<Node> = None;
+        nodes
+    }
 
-        for node in nodes {
-            let data = node.data().await?;
+    /// Gets an ideal node with the least amount of load that identifies as a bot, for Anchorages running several bots
+    pub async fn get_ideal_node_for_user(
+        &self,
+        user_id: impl Into<UserId>,
+    ) -> Result<Node, AnchorageError> {
+        let user_id = user_id.into();
 
-            if selected_node.is_none() {
-                selected_node = Some(node);
-                continue;
-            }
+        self.get_ideal_node_filtered(|data| data.id == user_id)
+            .await
+    }
+
+    /// Gets an ideal node with the least amount of load that belongs to a group
+    pub async fn get_ideal_node_in_group(&self, group: &str) -> Result<Node, AnchorageError> {
+        self.get_ideal_node_filtered(|data| data.groups.iter().any(|name| name == group))
+            .await
+    }
+
+    /// Gets an ideal node in the region of a Discord voice endpoint, walking its fallback chain when the region has no healthy node
+    /// # Healthy nodes are connected and not quarantined. Without a healthy node in the chain, any node is picked if the regions fall back to any
+    pub async fn get_ideal_node_for_endpoint(
+        &self,
+        endpoint: &str,
+    ) -> Result<Node, AnchorageError> {
+        let Some(regions) = &self.regions else {
+            return self.get_ideal_node().await;
+        };
+
+        for region in regions.chain_for(endpoint) {
+            let node = self
+                .get_ideal_node_filtered(|data| {
+                    data.state == NodeState::Connected
+                        && !data.quarantined
+                        && data.groups.iter().any(|name| name == region)
+                })
+                .await;
 
-            if penalties > data.penalties {
-                selected_node = Some(node);
+            if node.is_ok() {
+                return node;
             }
 
-            penalties = data.penalties;
+            tracing::debug!("Region {} has no healthy node, falling back", region);
         }
 
-        match selected_node {
-            Some(node) => Ok(node),
-            None => Err(AnchorageError::NoNodesAvailable),
+        if regions.fallback_to_any {
+            return self.get_ideal_node().await;
         }
+
+        Err(AnchorageError::NoNodesAvailable)
+    }
+
+    /// Gets the node a guild previously used if it's still available, otherwise an ideal node
+    pub async fn get_ideal_node_for_guild(
+        &self,
+        guild_id: impl Into<GuildId>,
+    ) -> Result<Node, AnchorageError> {
+        if let Some(name) = self.affinity.get(guild_id.into()).await
+            && let Some(node) = self.nodes.read_async(&name, |_, node| node.clone()).await
+            && !node.cached_data().draining
+        {
+            return Ok(node);
+        }
+
+        self.get_ideal_node().await
     }
 
     /// Gets the node where a player is connected to
-    pub async fn get_node_for_player(&self, guild_id: u64) -> Option<OccupiedEntry<String, Node>> {
-        self.nodes
-            .any_async(|_, node| node.events_sender.contains_sync(&guild_id))
+    pub async fn get_node_for_player(&self, guild_id: impl Into<GuildId>) -> Option<Node> {
+        let guild_id = guild_id.into();
+
+        self.get_node_by_filter(|node| node.events_sender.contains_sync(&guild_id))
             .await
     }
 
+    /// Gets the first node that passes the filter
+    pub async fn get_node_by_filter(&self, filter: impl Fn(&Node) -> bool) -> Option<Node> {
+        let mut found = None;
+
+        self.nodes
+            .iter_async(|_, node| {
+                if filter(node) {
+                    found = Some(node.clone());
+                    return false;
+                }
+
+                true
+            })
+            .await;
+
+        found
+    }
+
+    /// Gets the shard of a guild with Discord's formula
+    pub fn shard_for_guild(guild_id: impl Into<GuildId>, shard_count: u64) -> u64 {
+        shard::shard_for_guild(guild_id, shard_count)
+    }
+
+    /// Gateway payload (op 4) that joins a voice channel, or leaves the current one if there is no channel
+    pub fn voice_state_payload(
+        guild_id: impl Into<GuildId>,
+        channel_id: Option<ChannelId>,
+        self_deaf: bool,
+        self_mute: bool,
+    ) -> Value {
+        VoiceStateUpdate {
+            guild_id: guild_id.into(),
+            channel_id,
+            self_mute,
+            self_deaf,
+        }
+        .payload()
+    }
+
+    /// Routes a voice state update to the shard of its guild, send its payload on that shard to join or leave
+    pub fn route_voice_state(&self, update: VoiceStateUpdate) -> RoutedVoiceState {
+        RoutedVoiceState {
+            shard: self.shard_router.shard(update.guild_id),
+            update,
+        }
+    }
+
+    /// Presets players can apply by name
+    ///
+    /// Call [`Presets::load`] once to load the presets saved on the preset store, if there is one
+    pub fn presets(&self) -> &Presets {
+        &self.presets
+    }
+
     /// Creates a new player, that you can interact and listen on events
     pub async fn create_player(
         &self,
-        guild_id: u64,
+        guild_id: impl Into<GuildId>,
         node: Node,
         connection: impl Into<ConnectionOptions>,
-    ) -> Result<(Player, Receiver<EventType>), AnchorageError> {
+    ) -> Result<(Player, EventReceiver), AnchorageError> {
+        self.create_player_with_options(PlayerOptions {
+            node,
+            guild_id: guild_id.into(),
+            connection: connection.into(),
+            destroy_on_drop: false,
+            events: EventChannel::default(),
+            max_volume: None,
+            volume: None,
+            paused: None,
+            filters: None,
+            validate_filters: None,
+            resume_grace: None,
+        })
+        .await
+    }
+
+    /// Creates a new player with additional options, that you can interact and listen on events
+    pub async fn create_player_with_options(
+        &self,
+        mut options: PlayerOptions,
+    ) -> Result<(Player, EventReceiver), AnchorageError> {
+        options.max_volume.get_or_insert(self.max_volume);
+        options
+            .validate_filters
+            .get_or_insert(self.validate_filters);
+
+        if options.filters.is_none() {
+            options.filters = self.default_filters.as_deref().cloned();
+        }
+
+        options.connection.validate()?;
+
+        let guild_id = options.guild_id;
+        let node = options.node.clone();
+
         if self.get_node_for_player(guild_id).await.is_some() {
             return Err(AnchorageError::CreateExistingPlayer);
         }
 
-        let (player, events_sender, events_receiver) = Player::new(PlayerOptions {
-            node: node.clone(),
-            guild_id,
-            connection: connection.into(),
-        })
-        .await?;
+        // the new player takes over from the orphan of this guild
+        self.orphans.remove_async(&guild_id).await;
+
+        let (player, events_sender, events_receiver) = Player::new(options).await?;
 
         let _ = node
             .events_sender
             .insert_async(guild_id, events_sender)
             .await;
 
+        self.affinity.set(guild_id, node.name.clone()).await;
+
         Ok((player, events_receiver))
     }
 
+    /// Creates a player from a snapshot on a node, then resumes its playback where it was saved
+    pub async fn restore_player(
+        &self,
+        snapshot: PlayerSnapshot,
+        node: Node,
+    ) -> Result<(Player, EventReceiver), AnchorageError> {
+        let user_id = node.cached_data().id;
+
+        let connection = ConnectionOptions {
+            channel_id: snapshot.voice.channel_id,
+            endpoint: snapshot.voice.endpoint.clone(),
+            guild_id: snapshot.guild_id,
+            session_id: snapshot.voice.session_id.clone(),
+            token: snapshot.voice.token.clone(),
+            user_id,
+            self_deaf: snapshot.self_deaf,
+            self_mute: snapshot.self_mute,
+        };
+
+        let (player, events) = self
+            .create_player(snapshot.guild_id, node, connection)
+            .await?;
+
+        player.restore(snapshot).await?;
+
+        Ok((player, events))
+    }
+
+    /// Saves the sessions, players and queues of every node, then stops the nodes without destroying their players
+    ///
+    /// Resuming is enabled on nodes that have it disabled, so lavalink keeps their sessions until [`Anchorage::thaw`] connects again
+    pub async fn freeze(&self) -> Result<FrozenState, AnchorageError> {
+        let mut state = FrozenState::default();
+
+        for node in self.node_list().await {
+            if node.cached_data().resume_timeout.is_none() {
+                node.set_resuming(true, FREEZE_RESUME_TIMEOUT).await?;
+            }
+
+            let session_id = node.rest.get_session_id().await?;
+
+            let mut guilds = vec![];
+
+            node.events_sender
+                .iter_async(|guild_id, _| {
+                    guilds.push(*guild_id);
+                    true
+                })
+                .await;
+
+            for guild_id in guilds {
+                let mut snapshot: PlayerSnapshot = node.rest.get_player(guild_id).await?.into();
+
+                self.locations
+                    .read_async(&guild_id, |_, location| {
+                        location.fill_snapshot(&mut snapshot)
+                    })
+                    .await;
+
+                let queue = self.queues.get(guild_id).await?;
+
+                state.players.push(FrozenPlayer {
+                    node: node.name.clone(),
+                    snapshot,
+                    queue,
+                });
+            }
+
+            state.sessions.insert(node.name.clone(), session_id);
+        }
+
+        for node in self.node_list().await {
+            node.shutdown().await?;
+            self.nodes.remove_async(&node.name).await;
+        }
+
+        Ok(state)
+    }
+
+    /// Starts the nodes on the sessions of a frozen state, then recreates its players and queues
+    ///
+    /// Players lavalink still has keep playing, players it lost are restored from their snapshot.
+    /// Players whose node is gone are restored on an ideal node. Players that failed to thaw are skipped,
+    /// and the start result of each node is returned with the thawed players
+    pub async fn thaw(
+        &self,
+        user_id: impl Into<UserId>,
+        nodes_data: impl IntoIterator<Item = impl Into<NodeOptions>>,
+        state: FrozenState,
+    ) -> Result<ThawedState, AnchorageError> {
+        let nodes = nodes_data.into_iter().map(|data| {
+            let mut info: NodeOptions = data.into();
+
+            if let Some(session_id) = state.sessions.get(&info.name) {
+                info.session_id = Some(session_id.clone());
+            }
+
+            info
+        });
+
+        let nodes = self.start(user_id, nodes).await?;
+
+        let mut players = vec![];
+
+        for frozen in state.players {
+            let guild_id = frozen.snapshot.guild_id;
+
+            match self.thaw_player(frozen).await {
+                Ok(player) => players.push(player),
+                Err(error) => {
+                    tracing::warn!("Failed to thaw player of guild {} => {:?}", guild_id, error);
+                }
+            }
+        }
+
+        Ok(ThawedState { players, nodes })
+    }
+
+    /// Recreates a single frozen player and its queue
+    async fn thaw_player(
+        &self,
+        frozen: FrozenPlayer,
+    ) -> Result<(Player, EventReceiver), AnchorageError> {
+        let guild_id = frozen.snapshot.guild_id;
+
+        let node = match self
+            .nodes
+            .read_async(&frozen.node, |_, node| node.clone())
+            .await
+        {
+            Some(node) => node,
+            None => self.get_ideal_node().await?,
+        };
+
+        self.queues
+            .update(guild_id, |queue| *queue = frozen.queue)
+            .await?;
+
+        if node.rest.get_player(guild_id).await.is_err() {
+            return self.restore_player(frozen.snapshot, node).await;
+        }
+
+        let user_id = node.cached_data().id;
+        let snapshot = frozen.snapshot;
+        let voice = snapshot.voice;
+
+        // the player lives on, so it keeps its own filters instead of starting with the default ones
+        self.create_player_with_options(PlayerOptions {
+            node,
+            guild_id,
+            connection: ConnectionOptions {
+                channel_id: voice.channel_id,
+                endpoint: voice.endpoint,
+                guild_id,
+                session_id: voice.session_id,
+                token: voice.token,
+                user_id,
+                self_deaf: snapshot.self_deaf,
+                self_mute: snapshot.self_mute,
+            },
+            destroy_on_drop: false,
+            events: EventChannel::default(),
+            max_volume: None,
+            volume: Some(snapshot.volume),
+            paused: Some(snapshot.paused),
+            filters: Some(snapshot.filters),
+            validate_filters: None,
+            resume_grace: None,
+        })
+        .await
+    }
+
     /// Destroys an established player
-    pub async fn destroy_player(&self, guild_id: u64) -> Result<(), AnchorageError> {
+    pub async fn destroy_player(&self, guild_id: impl Into<GuildId>) -> Result<(), AnchorageError> {
+        let guild_id = guild_id.into();
+
+        // the node of an orphan is gone along with the player lavalink had
+        if let Some((_, orphan)) = self.orphans.remove_async(&guild_id).await {
+            orphan
+                .events_sender
+                .send(EventType::Destroyed(DestroyReason::Requested))
+                .await;
+
+            self.locations.remove_async(&guild_id).await;
+            self.queues.remove(guild_id).await?;
+        }
+
         let Some(node) = self.get_node_for_player(guild_id).await else {
             return Ok(());
         };
@@ -177,37 +975,408 @@ impl Anchorage {
         node.rest.destroy_player(guild_id).await?;
 
         if let Some(sender) = node.events_sender.get_async(&guild_id).await {
-            sender.send_async(EventType::Destroyed).await.ok();
+            sender
+                .send(EventType::Destroyed(DestroyReason::Requested))
+                .await;
         }
 
         node.events_sender.remove_async(&guild_id).await;
+        node.voices.remove_async(&guild_id).await;
+        node.now_playing.remove_async(&guild_id).await;
+        node.locations.remove_async(&guild_id).await;
+        node.queues.remove(guild_id).await?;
 
         Ok(())
     }
 
+    /// Moves a player to another node, keeping its playback, handles and event subscriber
+    pub async fn move_player(
+        &self,
+        guild_id: impl Into<GuildId>,
+        target: Node,
+    ) -> Result<(), AnchorageError> {
+        let guild_id = guild_id.into();
+
+        let Some(source) = self.get_node_for_player(guild_id).await else {
+            return Err(AnchorageError::PlayerNotFound(guild_id));
+        };
+
+        if source.name == target.name {
+            return Ok(());
+        }
+
+        // lavalink ties players to the bot the node is connected as
+        let source_id = source.cached_data().id;
+        let target_id = target.cached_data().id;
+
+        if source_id != target_id {
+            return Err(AnchorageError::NodeUserIdMismatch(
+                target.name,
+                target_id,
+                source_id,
+            ));
+        }
+
+        let player = source.rest.get_player(guild_id).await?;
+
+        let position = self
+            .locations
+            .read_async(&guild_id, |_, location| location.resume_grace())
+            .await
+            .flatten()
+            .and_then(|grace| resume_position(&player, &player.state, grace));
+
+        let mut snapshot: PlayerSnapshot = player.into();
+
+        if let Some(position) = position {
+            snapshot.position = position;
+        }
+
+        let voice = snapshot.voice.clone();
+
+        target
+            .rest
+            .update_player(guild_id, false, snapshot.into())
+            .await?;
+
+        self.hand_over(guild_id, &source, &target, voice).await;
+
+        // the player already plays on the target, so a failure here only leaves a stale player behind
+        if let Err(error) = source.rest.destroy_player(guild_id).await {
+            tracing::warn!(
+                "Failed to destroy moved player of guild {} on node {} => {:?}",
+                guild_id,
+                source.name,
+                error
+            );
+        }
+
+        if let Some(sender) = target.events_sender.get_async(&guild_id).await {
+            sender
+                .send(EventType::Moved {
+                    from: source.name.clone(),
+                    to: target.name.clone(),
+                })
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Points the handles, event subscriber and cached data of a player to the node it now plays on
+    async fn hand_over(
+        &self,
+        guild_id: GuildId,
+        source: &Node,
+        target: &Node,
+        voice: LavalinkVoice,
+    ) {
+        if let Some((_, sender)) = source.events_sender.remove_async(&guild_id).await {
+            target.events_sender.upsert_async(guild_id, sender).await;
+        }
+
+        source.voices.remove_async(&guild_id).await;
+        source.now_playing.remove_async(&guild_id).await;
+        target.voices.upsert_async(guild_id, voice).await;
+
+        self.locations
+            .read_async(&guild_id, |_, location| location.set(target.clone()))
+            .await;
+
+        self.affinity.set(guild_id, target.name.clone()).await;
+    }
+
+    /// Players of nodes that failed under [`NodeFailurePolicy::KeepOrphans`], to restore or destroy by hand
+    /// # Creating or destroying the player of an orphan's guild forgets the orphan
+    pub async fn orphaned_players(&self) -> Vec<OrphanedPlayer> {
+        let mut orphans = vec![];
+
+        self.orphans
+            .iter_async(|_, orphan| {
+                orphans.push(orphan.clone());
+                true
+            })
+            .await;
+
+        orphans
+    }
+
+    /// Keeps the players of a node that failed reachable once the node is removed
+    async fn keep_orphans(&self, failed: Node) {
+        let mut senders = vec![];
+
+        failed
+            .events_sender
+            .iter_async(|guild_id, sender| {
+                senders.push((*guild_id, sender.clone()));
+                true
+            })
+            .await;
+
+        for (guild_id, events_sender) in senders {
+            let orphan = OrphanedPlayer {
+                node: failed.name.clone(),
+                guild_id,
+                voice: failed
+                    .voices
+                    .read_async(&guild_id, |_, voice| voice.clone())
+                    .await,
+                track: failed
+                    .now_playing
+                    .read_async(&guild_id, |_, track| track.clone())
+                    .await,
+                events_sender,
+            };
+
+            tracing::warn!(
+                "Player of guild {} is orphaned by failed node {}",
+                guild_id,
+                failed.name
+            );
+
+            self.orphans.upsert_async(guild_id, orphan).await;
+        }
+    }
+
+    /// Moves the players of a node that failed to the ideal nodes of the same bot, destroying the ones that can't move
+    async fn migrate_players(&self, failed: Node) {
+        let mut guilds = vec![];
+
+        failed
+            .events_sender
+            .iter_async(|guild_id, _| {
+                guilds.push(*guild_id);
+                true
+            })
+            .await;
+
+        for guild_id in guilds {
+            let Err(error) = self.migrate_player(&failed, guild_id).await else {
+                continue;
+            };
+
+            tracing::warn!(
+                "Failed to migrate player of guild {} off failed node {} => {:?}",
+                guild_id,
+                failed.name,
+                error
+            );
+
+            failed.voices.remove_async(&guild_id).await;
+            failed.now_playing.remove_async(&guild_id).await;
+            failed.locations.remove_async(&guild_id).await;
+
+            if let Some((_, sender)) = failed.events_sender.remove_async(&guild_id).await {
+                sender
+                    .send(EventType::Destroyed(DestroyReason::NodeFailed))
+                    .await;
+            }
+        }
+    }
+
+    /// Creates a player of a failed node on another node, from its last voice data and track
+    ///
+    /// The failed node can't be asked for the player anymore, so the track starts over with the default volume and filters
+    async fn migrate_player(&self, failed: &Node, guild_id: GuildId) -> Result<(), AnchorageError> {
+        let Some(voice) = failed
+            .voices
+            .read_async(&guild_id, |_, voice| voice.clone())
+            .await
+        else {
+            return Err(AnchorageError::PlayerNotFound(guild_id));
+        };
+
+        let user_id = failed.cached_data().id;
+
+        let target = self
+            .get_ideal_node_filtered(|data| {
+                data.name != failed.name && data.id == user_id && data.state == NodeState::Connected
+            })
+            .await?;
+
+        let track = failed
+            .now_playing
+            .read_async(&guild_id, |_, track| track.clone())
+            .await;
+
+        let options = LavalinkPlayerOptions {
+            track: track.as_ref().map(UpdatePlayerTrack::from),
+            voice: Some(voice.clone()),
+            ..Default::default()
+        };
+
+        target.rest.update_player(guild_id, false, options).await?;
+
+        self.hand_over(guild_id, failed, &target, voice).await;
+
+        if let Some(sender) = target.events_sender.get_async(&guild_id).await {
+            sender
+                .send(EventType::Moved {
+                    from: failed.name.clone(),
+                    to: target.name.clone(),
+                })
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Moves players from the nodes with the most players to the ones with the least, up to a number of moves
+    ///
+    /// Only connected nodes are considered, and players only move between nodes of the same user id.
+    /// Draining and quarantined nodes never receive players. Returns the amount of players that were moved
+    pub async fn rebalance(&self, max_moves: usize) -> Result<usize, AnchorageError> {
+        // nodes of every user id, with their players and if they can receive players
+        let mut groups: HashMap<UserId, Vec<(Node, Vec<GuildId>, bool)>> = HashMap::new();
+
+        for node in self.node_list().await {
+            if !node.is_connected() {
+                continue;
+            }
+
+            let data = node.cached_data();
+            let mut guilds = vec![];
+
+            node.events_sender
+                .iter_async(|guild_id, _| {
+                    guilds.push(*guild_id);
+                    true
+                })
+                .await;
+
+            groups.entry(data.id).or_default().push((
+                node,
+                guilds,
+                !data.draining && !data.quarantined,
+            ));
+        }
+
+        let mut moves = 0;
+
+        for loads in groups.values_mut() {
+            while moves < max_moves {
+                let Some(busiest) = (0..loads.len()).max_by_key(|index| loads[*index].1.len())
+                else {
+                    break;
+                };
+
+                let Some(idlest) = (0..loads.len())
+                    .filter(|index| loads[*index].2)
+                    .min_by_key(|index| loads[*index].1.len())
+                else {
+                    break;
+                };
+
+                // moving would only swap which node has more players
+                if loads[busiest].1.len() <= loads[idlest].1.len() + 1 {
+                    break;
+                }
+
+                let Some(guild_id) = loads[busiest].1.pop() else {
+                    break;
+                };
+
+                let target = loads[idlest].0.clone();
+
+                if let Err(error) = self.move_player(guild_id, target).await {
+                    tracing::warn!(
+                        "Failed to move player of guild {} while rebalancing => {:?}",
+                        guild_id,
+                        error
+                    );
+
+                    continue;
+                }
+
+                loads[idlest].1.push(guild_id);
+                moves += 1;
+            }
+        }
+
+        Ok(moves)
+    }
+
     /// Connects a disconnected node that is in cache
     pub async fn connect(&self, name: &str) -> Result<(), AnchorageError> {
-        if let Some(mut data) = self.nodes.get_async(name).await {
-            let node = data.get_mut();
-            node.connect().await?;
-        }
+        self.node_by_name(name).await?.connect().await?;
 
         Ok(())
     }
 
     /// Disconnects a connected node, then removes it from cache
     pub async fn disconnect(&self, name: &str, destroy: bool) -> Result<(), AnchorageError> {
-        if let Some(mut data) = self.nodes.get_async(name).await {
-            let node = data.get_mut();
+        let node = self.node_by_name(name).await?;
 
-            node.disconnect().await?;
+        node.disconnect().await?;
 
-            if destroy {
-                node.destroy().await?;
-                self.nodes.remove_async(name).await;
-            }
+        if destroy {
+            node.destroy().await?;
+            self.nodes.remove_async(name).await;
         }
 
         Ok(())
     }
+
+    /// If a node with this name exists
+    pub async fn has_node(&self, name: &str) -> bool {
+        self.nodes.contains_async(name).await
+    }
+
+    /// Every node along with its name, sorted by name
+    pub async fn nodes_snapshot(&self) -> Vec<(String, Node)> {
+        let mut nodes = vec![];
+
+        self.nodes
+            .iter_async(|name, node| {
+                nodes.push((name.clone(), node.clone()));
+                true
+            })
+            .await;
+
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        nodes
+    }
+
+    /// Every node sorted from the least to the most loaded, the way ideal nodes are picked
+    /// # Quarantined nodes come after every other node, and draining nodes after those
+    pub async fn nodes_by_penalty(&self) -> Vec<Node> {
+        let mut nodes = self
+            .node_list()
+            .await
+            .into_iter()
+            .map(|node| {
+                let data = node.cached_data();
+                ((data.draining, data.quarantined, data.penalties), node)
+            })
+            .collect::<Vec<_>>();
+
+        nodes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        nodes.into_iter().map(|(_, node)| node).collect()
+    }
+
+    /// Names of every node, sorted
+    pub async fn node_names(&self) -> Vec<String> {
+        let mut names = vec![];
+
+        self.nodes
+            .iter_async(|name, _| {
+                names.push(name.clone());
+                true
+            })
+            .await;
+
+        names.sort();
+
+        names
+    }
+
+    /// Gets a node by name, erroring if there is none
+    async fn node_by_name(&self, name: &str) -> Result<Node, AnchorageError> {
+        self.nodes
+            .read_async(name, |_, node| node.clone())
+            .await
+            .ok_or_else(|| AnchorageError::NodeNotFound(name.to_string()))
+    }
 }
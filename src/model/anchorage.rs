@@ -1,59 +1,634 @@
-use reqwest::Client;
-use reqwest::Client as ReqwestClient;
+use flume::Sender as FlumeSender;
+#[cfg(feature = "reqwest")]
+use reqwest::{Client, ClientBuilder};
+use scc::HashMap as ConcurrentHashMap;
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+#[cfg(feature = "debug-recorder")]
+use std::path::{Path, PathBuf};
+use std::result::Result;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderName, HeaderValue};
+use tokio_util::sync::CancellationToken;
+use url::Url;
 
+use crate::affinity::NodeAffinity;
+use crate::audit::AuditSink;
+use crate::model::error::{AnchorageError, LavalinkPlayerError};
+use crate::model::gateway::VoiceStateUpdate;
+use crate::model::id::{ChannelId, GuildId, UserId};
+use crate::model::node::{Cpu, Memory};
+use crate::model::player::{LavalinkFilters, LavalinkVoice, PlayerSnapshot, Track};
+use crate::node::cache::ResolveCache;
 use crate::node::client::Node;
+#[cfg(feature = "debug-recorder")]
+use crate::node::recorder::TrafficRecorder;
+use crate::node::scheduler::RestScheduler;
+use crate::node::transport::{HttpTransport, WsTransport};
+use crate::player::Player;
+use crate::player::PlayerLocation;
+use crate::player::events::{EventChannel, EventReceiver, EventSender};
+use crate::preset::{PresetStore, Presets};
+use crate::queue::{Autoplay, Queue, QueueStore, Queues};
+use crate::shard::ShardRouter;
 
 /// Options to initialize an internal NodeManager
 pub struct NodeManagerOptions<'a> {
     pub name: &'a str,
-    pub host: &'a str,
-    pub port: u32,
+    pub websocket_url: Url,
+    pub rest_url: Url,
+    pub headers: HeaderMap,
     pub auth: &'a str,
-    pub id: u64,
-    pub request: ReqwestClient,
+    pub id: UserId,
+    pub transport: Arc<dyn HttpTransport>,
+    /// If the transport was supplied by the user, the TLS options then only apply to the websocket
+    pub custom_transport: bool,
+    pub ws_transport: Arc<dyn WsTransport>,
     pub user_agent: &'a str,
+    pub client_name: &'a str,
     pub reconnect_tries: u16,
+    pub tls: Option<&'a TlsOptions>,
+    pub http: &'a HttpOptions,
+    pub groups: Vec<String>,
+    pub resume_timeout: Option<u32>,
+    pub stats_history: usize,
+    pub stable_after: Duration,
+    pub min_version: Option<VersionReq>,
+    pub session_id: Option<String>,
+    pub degradation: DegradationOptions,
+    pub quarantine: Option<QuarantineOptions>,
+    pub voice_close_policy: VoiceClosePolicy,
+    pub failure_policy: NodeFailurePolicy,
+    pub cancellation_token: CancellationToken,
+    pub queues: Queues,
+    pub presets: Presets,
+    pub(crate) locations: Arc<ConcurrentHashMap<GuildId, PlayerLocation>>,
+    pub resolve_cache: Option<Arc<ResolveCache>>,
+    pub rest_scheduler: Option<Arc<RestScheduler>>,
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "debug-recorder")]
+    pub record_traffic: Option<&'a Path>,
 }
 
 /// Options to initialize a Rest client
 pub struct RestOptions<'a> {
-    pub request: Client,
+    pub transport: Arc<dyn HttpTransport>,
     pub url: String,
-    pub auth: &'a str,
+    pub headers: HeaderMap,
+    pub auth: Arc<RwLock<String>>,
     pub user_agent: &'a str,
     pub session_id: Arc<RwLock<Option<String>>>,
+    /// Notified when lavalink reports that the session of this rest no longer exists
+    pub session_invalidated: Option<FlumeSender<()>>,
+    pub resolve_cache: Option<Arc<ResolveCache>>,
+    pub scheduler: Option<Arc<RestScheduler>>,
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "debug-recorder")]
+    pub recorder: Option<Arc<TrafficRecorder>>,
 }
 
 /// Options to create a player
 pub struct PlayerOptions {
     pub node: Node,
     pub connection: ConnectionOptions,
-    pub guild_id: u64,
+    pub guild_id: GuildId,
+    /// Destroys the player on lavalink once every handle of it is dropped
+    pub destroy_on_drop: bool,
+    /// Channel the events of this player are delivered through
+    pub events: EventChannel,
+    /// Highest volume this player can be set to, the one of Anchorage if none
+    pub max_volume: Option<u32>,
+    /// Volume the player starts at, 100 if none
+    pub volume: Option<u32>,
+    /// Starts the player paused
+    pub paused: Option<bool>,
+    /// Filters the player starts with, the default filters of Anchorage if none
+    pub filters: Option<LavalinkFilters>,
+    /// Rejects filters the node has disabled when updating filters, the setting of Anchorage if none
+    pub validate_filters: Option<bool>,
+    /// Seeks to where the track was, minus this grace, once the player moved or its node resumed. Disabled if none
+    pub resume_grace: Option<Duration>,
 }
 
 /// Options to be used to connect to a voice channel
 pub struct ConnectionOptions {
-    pub channel_id: u64,
+    pub channel_id: ChannelId,
     pub endpoint: String,
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub session_id: String,
     pub token: String,
-    pub user_id: u64,
+    pub user_id: UserId,
+    /// If the bot joined deafened, sent along when rejoining through [`ConnectionOptions::voice_state`]
+    pub self_deaf: bool,
+    /// If the bot joined muted, sent along when rejoining through [`ConnectionOptions::voice_state`]
+    pub self_mute: bool,
+}
+
+impl ConnectionOptions {
+    /// Voice state update that joins the channel of this connection with the same deaf and mute state
+    pub fn voice_state(&self) -> VoiceStateUpdate {
+        VoiceStateUpdate {
+            guild_id: self.guild_id,
+            channel_id: Some(self.channel_id),
+            self_mute: self.self_mute,
+            self_deaf: self.self_deaf,
+        }
+    }
+
+    /// Checks the voice data is complete, normalizing the endpoint to the bare host lavalink expects
+    /// # Discord sometimes sends endpoints with a `wss://` scheme or a port, both are stripped
+    pub fn validate(&mut self) -> Result<(), LavalinkPlayerError> {
+        let mut endpoint = self.endpoint.trim();
+
+        for scheme in ["wss://", "ws://", "https://"] {
+            endpoint = endpoint.strip_prefix(scheme).unwrap_or(endpoint);
+        }
+
+        endpoint = endpoint.trim_end_matches('/');
+
+        if let Some((host, port)) = endpoint.rsplit_once(':')
+            && port.parse::<u16>().is_ok()
+        {
+            endpoint = host;
+        }
+
+        self.endpoint = endpoint.to_string();
+
+        if self.endpoint.is_empty() {
+            return Err(LavalinkPlayerError::InvalidVoiceData(
+                "endpoint is empty".into(),
+            ));
+        }
+
+        if self.token.trim().is_empty() {
+            return Err(LavalinkPlayerError::InvalidVoiceData(
+                "token is empty".into(),
+            ));
+        }
+
+        if self.session_id.trim().is_empty() {
+            return Err(LavalinkPlayerError::InvalidVoiceData(
+                "session id is empty".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// State of a running Anchorage, saved to continue playback on another process
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FrozenState {
+    /// Session of each node, mapped by node name
+    pub sessions: HashMap<String, String>,
+    pub players: Vec<FrozenPlayer>,
+}
+
+/// Player saved in a frozen state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FrozenPlayer {
+    /// Name of the node the player was on
+    pub node: String,
+    pub snapshot: PlayerSnapshot,
+    pub queue: Queue,
+}
+
+/// Player of a node that failed under [`NodeFailurePolicy::KeepOrphans`], kept to be restored or destroyed by hand
+#[derive(Clone, Debug)]
+pub struct OrphanedPlayer {
+    /// Name of the node that failed
+    pub node: String,
+    pub guild_id: GuildId,
+    /// Last voice data sent for the player, none if it never connected
+    pub voice: Option<LavalinkVoice>,
+    /// Track the player was playing, none if nothing was
+    pub track: Option<Track>,
+    /// Subscriber of the player, told once the orphan is destroyed
+    pub(crate) events_sender: EventSender,
+}
+
+/// Result of [`crate::Anchorage::thaw`]
+pub struct ThawedState {
+    /// Players that were thawed, with their event receivers
+    pub players: Vec<(Player, EventReceiver)>,
+    /// Start result of each node, mapped by node name
+    pub nodes: HashMap<String, Result<(), AnchorageError>>,
 }
 
 /// User node options used to create a node
+#[derive(Clone, Default)]
 pub struct NodeOptions {
     pub name: String,
     pub host: String,
     pub port: u32,
     pub auth: String,
+    /// Connects to this node using https and wss
+    pub secure: bool,
+    /// Custom TLS configuration for this node, used on both REST and websocket
+    pub tls: Option<TlsOptions>,
+    /// Extra headers sent on the websocket handshake and on every REST request of this node
+    pub headers: HashMap<String, String>,
+    /// Named groups this node belongs to, used to dedicate nodes to specific guilds or shards
+    pub groups: Vec<String>,
+    /// Enables resuming with this timeout (in seconds) every time the node gets ready.
+    /// Players are kept through disconnects shorter than it, instead of being destroyed. Falls back to the resume options of Anchorage if none
+    pub resume_timeout: Option<u32>,
+    /// Amount of statistics payloads kept in the history of this node, 30 if none
+    pub stats_history: Option<usize>,
+    /// Session to resume on the first connect, like one saved by [`crate::Anchorage::freeze`]
+    pub session_id: Option<String>,
+    /// User-Id this node identifies with, to run nodes of several bots on one Anchorage.
+    /// The one Anchorage was started with if none
+    pub user_id: Option<UserId>,
+    /// Thresholds of the degradation alerts of this node, defaults to [`DegradationOptions::default`]
+    pub degradation: Option<DegradationOptions>,
+    /// Quarantines this node while it's unhealthy, disabled if none
+    pub quarantine: Option<QuarantineOptions>,
+    /// Time the websocket has to stay connected before the reconnect attempts reset, 60 seconds if none
+    pub stable_after: Option<Duration>,
+    /// Lavalink versions this node has to run (e.g. `>=4.0.8`), the node is refused after connecting otherwise. Any version if none
+    pub min_version: Option<VersionReq>,
+    /// Records the raw traffic of this node to this NDJSON file, appending to it, for debugging only
+    #[cfg(feature = "debug-recorder")]
+    pub record_traffic: Option<PathBuf>,
+}
+
+impl NodeOptions {
+    /// Creates new node options, validating the address of the node early
+    pub fn new(
+        name: impl Into<String>,
+        host: impl Into<String>,
+        port: u32,
+        auth: impl Into<String>,
+    ) -> Result<Self, AnchorageError> {
+        let options = Self {
+            name: name.into(),
+            host: host.into(),
+            port,
+            auth: auth.into(),
+            ..Default::default()
+        };
+
+        options.validate()?;
+
+        Ok(options)
+    }
+
+    /// Checks if the host, port and headers of this node are valid
+    pub fn validate(&self) -> Result<(), AnchorageError> {
+        self.rest_url()?;
+        self.header_map()?;
+
+        Ok(())
+    }
+
+    /// Extra headers of this node, parsed to be used on requests
+    pub fn header_map(&self) -> Result<HeaderMap, AnchorageError> {
+        let mut headers = HeaderMap::new();
+
+        for (name, value) in &self.headers {
+            let invalid = || AnchorageError::InvalidNodeHeader(name.clone());
+
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+                HeaderValue::from_str(value).map_err(|_| invalid())?,
+            );
+        }
+
+        Ok(headers)
+    }
+
+    /// Base REST url of this node
+    pub fn rest_url(&self) -> Result<Url, AnchorageError> {
+        let scheme = if self.secure { "https" } else { "http" };
+
+        self.build_url(scheme, "/v4")
+    }
+
+    /// Websocket url of this node
+    pub fn websocket_url(&self) -> Result<Url, AnchorageError> {
+        let scheme = if self.secure { "wss" } else { "ws" };
+
+        self.build_url(scheme, "/v4/websocket")
+    }
+
+    /// Builds an url for this node, bracketing the host if it's an IPv6 address
+    fn build_url(&self, scheme: &str, path: &str) -> Result<Url, AnchorageError> {
+        let invalid = || AnchorageError::InvalidNodeAddress(format!("{}:{}", self.host, self.port));
+
+        let port = u16::try_from(self.port)
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(invalid)?;
+
+        let mut url = Url::parse(&format!("{scheme}://localhost")).map_err(|_| invalid())?;
+
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+
+        match host.parse::<IpAddr>() {
+            Ok(address) => url.set_ip_host(address).map_err(|_| invalid())?,
+            Err(_) => url.set_host(Some(host)).map_err(|_| invalid())?,
+        }
+
+        url.set_port(Some(port)).map_err(|_| invalid())?;
+        url.set_path(path);
+
+        Ok(url)
+    }
+}
+
+/// TLS options to use when connecting to a node
+#[derive(Clone, Debug, Default)]
+pub struct TlsOptions {
+    /// Accepts any certificate the node presents, including self-signed ones. Use with care
+    pub danger_accept_invalid_certs: bool,
+    /// Additional PEM encoded root certificates to trust, for nodes behind an internal PKI
+    pub root_certificates: Vec<Vec<u8>>,
 }
 
 /// Options to initialize an Anchorage client
+#[derive(Default)]
 pub struct Options {
     pub user_agent: Option<String>,
+    /// Client-Name sent to the nodes, must be in `Name/Version` format
+    pub client_name: Option<String>,
     pub reconnect_tries: Option<u16>,
+    #[cfg(feature = "reqwest")]
     pub request: Option<Client>,
+    /// Transport REST requests are sent through instead of reqwest (e.g. hyper or a test double), takes precedence over `request`
+    pub http_transport: Option<Arc<dyn HttpTransport>>,
+    /// Transport the websockets of nodes run on (e.g. unix sockets or an in-memory test transport), defaults to TCP
+    pub ws_transport: Option<Arc<dyn WsTransport>>,
+    /// Connection tuning of the request client, used when Anchorage builds it instead of taking `request`
+    pub http: Option<HttpOptions>,
+    /// Storage for guild to node affinity, defaults to an in-memory storage
+    pub affinity: Option<Arc<dyn NodeAffinity>>,
+    /// How nodes react to voice websocket closes, defaults to [`VoiceClosePolicy::default`]
+    pub voice_close_policy: Option<VoiceClosePolicy>,
+    /// What happens to the players of a node that gave up reconnecting, they are destroyed by default
+    pub node_failure_policy: Option<NodeFailurePolicy>,
+    /// Shuts down every node gracefully once cancelled
+    pub cancellation_token: Option<CancellationToken>,
+    /// Storage for player queues, defaults to an in-memory storage
+    pub queue_store: Option<Arc<dyn QueueStore>>,
+    /// Picks tracks for queues with autoplay enabled once they run out
+    pub autoplay: Option<Arc<dyn Autoplay>>,
+    /// Caches resolve results across every node, disabled if none
+    pub resolve_cache: Option<ResolveCacheOptions>,
+    /// Queues the REST requests of every node by priority to smooth out bursts, disabled if none
+    pub rest_scheduler: Option<RestSchedulerOptions>,
+    /// Highest volume players can be set to, defaults to 1000 (the limit of lavalink)
+    pub max_volume: Option<u32>,
+    /// Filters every new player starts with (e.g. a global equalizer profile), none if none
+    pub default_filters: Option<LavalinkFilters>,
+    /// Rejects filters the node has disabled when players update filters, instead of lavalink ignoring them. Disabled by default
+    pub validate_filters: Option<bool>,
+    /// Storage for presets, presets are only kept in memory if none
+    pub preset_store: Option<Arc<dyn PresetStore>>,
+    /// Picks the shard voice state updates are sent on, defaults to a single shard
+    pub shard_router: Option<Arc<dyn ShardRouter>>,
+    /// Receives every REST request and websocket message (e.g. for compliance records), none if none
+    pub audit_sink: Option<Arc<dyn AuditSink>>,
+    /// Resuming every node uses unless it sets its own `resume_timeout`, disabled if none
+    pub resume: Option<ResumeOptions>,
+    /// Regions nodes are grouped in, used by [`crate::Anchorage::get_ideal_node_for_endpoint`]. No regions if none
+    pub regions: Option<RegionOptions>,
+}
+
+/// Region of a cluster, made of the nodes that have its name in their groups
+#[derive(Clone, Debug, Default)]
+pub struct Region {
+    pub name: String,
+    /// Parts of the hosts of the Discord voice servers this region serves (e.g. `rotterdam` or `us-east`)
+    pub endpoints: Vec<String>,
+    /// Regions tried in order when this one has no healthy node
+    pub fallback: Vec<String>,
+}
+
+/// Regions of a cluster, so players connect to nodes close to their voice server
+#[derive(Clone, Debug, Default)]
+pub struct RegionOptions {
+    pub regions: Vec<Region>,
+    /// Picks any node once no region of the fallback chain has a healthy node, instead of failing
+    pub fallback_to_any: bool,
+}
+
+impl RegionOptions {
+    /// Regions to try for a voice endpoint in order, the one it belongs to followed by its fallbacks
+    pub fn chain_for(&self, endpoint: &str) -> Vec<&str> {
+        let host = endpoint.to_lowercase();
+
+        let Some(region) = self.regions.iter().find(|region| {
+            region
+                .endpoints
+                .iter()
+                .any(|part| host.contains(&part.to_lowercase()))
+        }) else {
+            return vec![];
+        };
+
+        let mut chain = vec![region.name.as_str()];
+
+        for name in &region.fallback {
+            if !chain.contains(&name.as_str()) {
+                chain.push(name);
+            }
+        }
+
+        chain
+    }
+}
+
+/// Resume settings applied to every node
+#[derive(Clone, Copy, Debug)]
+pub struct ResumeOptions {
+    pub enabled: bool,
+    /// Seconds lavalink keeps the session after a disconnect
+    pub timeout: u32,
+}
+
+impl ResumeOptions {
+    /// Resume timeout these options give nodes, none if resuming is disabled
+    pub fn timeout(&self) -> Option<u32> {
+        self.enabled.then_some(self.timeout)
+    }
+}
+
+/// Connection tuning of the request clients Anchorage builds, the defaults of reqwest are kept for fields that are none
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpOptions {
+    /// Maximum idle connections kept open per node
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open
+    pub pool_idle_timeout: Option<Duration>,
+    /// Interval of TCP keepalive probes on open connections
+    pub tcp_keepalive: Option<Duration>,
+    /// Sends small requests like player updates right away instead of buffering them
+    pub tcp_nodelay: Option<bool>,
+    /// Talks HTTP/2 right away without negotiating it, only for nodes that serve HTTP/2 (e.g. behind a proxy)
+    pub http2_prior_knowledge: bool,
+    /// Interval of HTTP/2 keepalive pings on open connections
+    pub http2_keep_alive_interval: Option<Duration>,
+}
+
+impl HttpOptions {
+    /// Applies these options to a request client builder
+    #[cfg(feature = "reqwest")]
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+
+        if let Some(enabled) = self.tcp_nodelay {
+            builder = builder.tcp_nodelay(enabled);
+        }
+
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+
+        builder
+    }
+}
+
+/// Options of the cache for resolve results
+#[derive(Clone, Debug)]
+pub struct ResolveCacheOptions {
+    /// Maximum amount of identifiers cached, the least recently used one is evicted past this
+    pub capacity: usize,
+    /// How long a result stays cached
+    pub ttl: Duration,
+}
+
+impl Default for ResolveCacheOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Options of the scheduler shared by the REST clients of every node
+#[derive(Clone, Debug)]
+pub struct RestSchedulerOptions {
+    /// Maximum amount of requests sent at the same time, the rest wait by priority
+    pub max_concurrent: usize,
+    /// Minimum time between the start of two requests
+    pub min_interval: Duration,
+}
+
+impl Default for RestSchedulerOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 16,
+            min_interval: Duration::ZERO,
+        }
+    }
+}
+
+/// Frame statistics thresholds a node is considered degraded past
+#[derive(Clone, Debug)]
+pub struct DegradationOptions {
+    /// Frame deficit per minute a statistics payload has to exceed
+    pub deficit: i32,
+    /// Nulled frames per minute a statistics payload has to exceed
+    pub nulled: u32,
+    /// Statistics payloads in a row that have to exceed a threshold before alerting
+    pub consecutive: u32,
+}
+
+impl Default for DegradationOptions {
+    fn default() -> Self {
+        Self {
+            deficit: 250,
+            nulled: 250,
+            consecutive: 3,
+        }
+    }
+}
+
+/// Thresholds a node is quarantined past, leaving it out of ideal node selection for a while
+#[derive(Clone, Debug)]
+pub struct QuarantineOptions {
+    /// Penalties a statistics payload has to exceed
+    pub max_penalties: f64,
+    /// Failed connection attempts in a row before quarantining
+    pub max_failures: u32,
+    /// How long the first quarantine lasts, doubled on each quarantine in a row
+    pub duration: Duration,
+    /// Longest a quarantine can last
+    pub max_duration: Duration,
+}
+
+impl Default for QuarantineOptions {
+    fn default() -> Self {
+        Self {
+            max_penalties: 500.0,
+            max_failures: 3,
+            duration: Duration::from_secs(30),
+            max_duration: Duration::from_secs(600),
+        }
+    }
+}
+
+/// How nodes react to the voice websocket close codes lavalink reports
+#[derive(Clone, Debug)]
+pub struct VoiceClosePolicy {
+    /// Close codes where the cached voice data of the player is sent again (4006 and 4009 by default)
+    pub resend_codes: Vec<usize>,
+    /// Close codes where the player is considered disconnected from voice (4014 by default)
+    pub disconnect_codes: Vec<usize>,
+}
+
+impl Default for VoiceClosePolicy {
+    fn default() -> Self {
+        Self {
+            resend_codes: vec![4006, 4009],
+            disconnect_codes: vec![4014],
+        }
+    }
+}
+
+/// What happens to the players of a node that threw an unrecoverable error or gave up reconnecting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodeFailurePolicy {
+    /// Players receive a destroy event
+    #[default]
+    DestroyPlayers,
+    /// Players are kept as orphans, to be restored or destroyed by hand (see [`crate::Anchorage::orphaned_players`])
+    KeepOrphans,
+    /// Players are moved to the ideal node of the same bot, the ones that can't move are destroyed
+    MigratePlayers,
+}
+
+/// Statistics of every node merged together
+#[derive(Default, Clone, Debug)]
+pub struct ClusterStats {
+    /// Amount of nodes that has statistics
+    pub nodes: u32,
+    /// Total players in the cluster
+    pub players: u32,
+    /// Total players that are playing in the cluster
+    pub playing_players: u32,
+    /// Total memory of the cluster
+    pub memory: Memory,
+    /// Total cores, and average load of the cluster
+    pub cpu: Cpu,
 }
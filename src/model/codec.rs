@@ -0,0 +1,303 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Map, Value};
+use std::result::Result;
+
+use super::error::TrackCodecError;
+use super::player::{Track, TrackInfo};
+
+/// Flag set on the message header when the track info has a version byte
+const TRACK_INFO_VERSIONED: u32 = 1;
+/// Latest track info version lavaplayer writes
+const TRACK_INFO_VERSION: u8 = 3;
+/// Sources that don't write any source specific data, so they can be encoded locally
+const ENCODABLE_SOURCES: [&str; 6] = [
+    "youtube",
+    "soundcloud",
+    "bandcamp",
+    "twitch",
+    "vimeo",
+    "nico",
+];
+
+impl Track {
+    /// Decodes a track locally from lavaplayer's base64 format, without asking a node
+    pub fn decode(encoded: &str) -> Result<Self, TrackCodecError> {
+        let bytes = STANDARD.decode(encoded)?;
+        let mut reader = Reader { bytes: &bytes };
+
+        let header = reader.read_u32()?;
+        let size = (header & 0x3FFF_FFFF) as usize;
+        let flags = header >> 30;
+
+        let mut reader = Reader {
+            bytes: reader.take(size)?,
+        };
+
+        let version = if flags & TRACK_INFO_VERSIONED != 0 {
+            reader.read_u8()?
+        } else {
+            1
+        };
+
+        if version == 0 || version > TRACK_INFO_VERSION {
+            return Err(TrackCodecError::UnsupportedVersion(version));
+        }
+
+        let title = reader.read_utf()?;
+        let author = reader.read_utf()?;
+        let length = reader.read_u64()?;
+        let identifier = reader.read_utf()?;
+        let is_stream = reader.read_u8()? != 0;
+        let uri = if version >= 2 {
+            reader.read_nullable_utf()?
+        } else {
+            None
+        };
+        let (artwork_url, isrc) = if version >= 3 {
+            (reader.read_nullable_utf()?, reader.read_nullable_utf()?)
+        } else {
+            (None, None)
+        };
+        let source_name = reader.read_utf()?;
+
+        // source specific data sits between the source name and the position
+        let position = Reader {
+            bytes: reader.take_last(8)?,
+        }
+        .read_u64()?;
+
+        Ok(Self {
+            encoded: encoded.to_string(),
+            info: TrackInfo {
+                identifier,
+                is_seekable: !is_stream,
+                author,
+                length,
+                is_stream,
+                position,
+                title,
+                uri,
+                artwork_url,
+                isrc,
+                source_name,
+            },
+            plugin_info: Value::Object(Map::new()),
+            user_data: None,
+        })
+    }
+}
+
+impl TrackInfo {
+    /// Encodes this track info locally into lavaplayer's base64 format
+    ///
+    /// Only sources without source specific data can be encoded, like youtube and soundcloud
+    pub fn encode(&self) -> Result<String, TrackCodecError> {
+        if !ENCODABLE_SOURCES.contains(&self.source_name.as_str()) {
+            return Err(TrackCodecError::UnsupportedSource(self.source_name.clone()));
+        }
+
+        let mut body = vec![TRACK_INFO_VERSION];
+
+        write_utf(&mut body, &self.title)?;
+        write_utf(&mut body, &self.author)?;
+        body.extend_from_slice(&self.length.to_be_bytes());
+        write_utf(&mut body, &self.identifier)?;
+        body.push(self.is_stream as u8);
+        write_nullable_utf(&mut body, self.uri.as_deref())?;
+        write_nullable_utf(&mut body, self.artwork_url.as_deref())?;
+        write_nullable_utf(&mut body, self.isrc.as_deref())?;
+        write_utf(&mut body, &self.source_name)?;
+        body.extend_from_slice(&self.position.to_be_bytes());
+
+        let header = (TRACK_INFO_VERSIONED << 30) | body.len() as u32;
+
+        let mut bytes = header.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&body);
+
+        Ok(STANDARD.encode(bytes))
+    }
+}
+
+/// Reads big endian values the way java's DataInput does
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], TrackCodecError> {
+        if self.bytes.len() < length {
+            return Err(TrackCodecError::UnexpectedEnd);
+        }
+
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn take_last(&mut self, length: usize) -> Result<&'a [u8], TrackCodecError> {
+        if self.bytes.len() < length {
+            return Err(TrackCodecError::UnexpectedEnd);
+        }
+
+        let (rest, taken) = self.bytes.split_at(self.bytes.len() - length);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, TrackCodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, TrackCodecError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, TrackCodecError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, TrackCodecError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Reads a length prefixed string in java's modified UTF-8
+    fn read_utf(&mut self) -> Result<String, TrackCodecError> {
+        let length = self.read_u16()? as usize;
+        let bytes = self.take(length)?;
+
+        let mut units = Vec::with_capacity(length);
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let byte = bytes[index] as u16;
+
+            let (unit, size) = if byte & 0x80 == 0 {
+                (byte, 1)
+            } else if byte & 0xE0 == 0xC0 && index + 1 < bytes.len() {
+                let second = bytes[index + 1] as u16;
+                (((byte & 0x1F) << 6) | (second & 0x3F), 2)
+            } else if byte & 0xF0 == 0xE0 && index + 2 < bytes.len() {
+                let second = bytes[index + 1] as u16;
+                let third = bytes[index + 2] as u16;
+                (
+                    ((byte & 0x0F) << 12) | ((second & 0x3F) << 6) | (third & 0x3F),
+                    3,
+                )
+            } else {
+                return Err(TrackCodecError::InvalidString);
+            };
+
+            units.push(unit);
+            index += size;
+        }
+
+        String::from_utf16(&units).map_err(|_| TrackCodecError::InvalidString)
+    }
+
+    fn read_nullable_utf(&mut self) -> Result<Option<String>, TrackCodecError> {
+        if self.read_u8()? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(self.read_utf()?))
+    }
+}
+
+/// Writes a length prefixed string in java's modified UTF-8
+fn write_utf(bytes: &mut Vec<u8>, value: &str) -> Result<(), TrackCodecError> {
+    let mut encoded = Vec::with_capacity(value.len());
+
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => encoded.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                encoded.push((0xC0 | (unit >> 6)) as u8);
+                encoded.push((0x80 | (unit & 0x3F)) as u8);
+            }
+            _ => {
+                encoded.push((0xE0 | (unit >> 12)) as u8);
+                encoded.push((0x80 | ((unit >> 6) & 0x3F)) as u8);
+                encoded.push((0x80 | (unit & 0x3F)) as u8);
+            }
+        }
+    }
+
+    let length = u16::try_from(encoded.len()).map_err(|_| TrackCodecError::InvalidString)?;
+
+    bytes.extend_from_slice(&length.to_be_bytes());
+    bytes.extend_from_slice(&encoded);
+
+    Ok(())
+}
+
+fn write_nullable_utf(bytes: &mut Vec<u8>, value: Option<&str>) -> Result<(), TrackCodecError> {
+    let Some(value) = value else {
+        bytes.push(0);
+        return Ok(());
+    };
+
+    bytes.push(1);
+    write_utf(bytes, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Track lavalink encoded, from the examples of its documentation (track info version 2)
+    const RICK_ROLL: &str = "QAAAjQIAJVJpY2sgQXN0bGV5IC0gTmV2ZXIgR29ubmEgR2l2ZSBZb3UgVXAADlJpY2tBc3RsZXlWRVZPAAAAAAADPCAAC2RRdzR3OVdnWGNRAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9ZFF3NHc5V2dYY1EAB3lvdXR1YmUAAAAAAAAAAA==";
+
+    #[test]
+    fn decodes_a_lavalink_track() {
+        let track = Track::decode(RICK_ROLL).unwrap();
+
+        assert_eq!(track.encoded, RICK_ROLL);
+        assert_eq!(track.info.title, "Rick Astley - Never Gonna Give You Up");
+        assert_eq!(track.info.author, "RickAstleyVEVO");
+        assert_eq!(track.info.length, 212000);
+        assert_eq!(track.info.identifier, "dQw4w9WgXcQ");
+        assert!(!track.info.is_stream);
+        assert!(track.info.is_seekable);
+        assert_eq!(
+            track.info.uri.as_deref(),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(track.info.artwork_url, None);
+        assert_eq!(track.info.isrc, None);
+        assert_eq!(track.info.source_name, "youtube");
+        assert_eq!(track.info.position, 0);
+    }
+
+    #[test]
+    fn encoded_track_decodes_to_the_same_info() {
+        let mut info = Track::decode(RICK_ROLL).unwrap().info;
+        info.artwork_url = Some(String::from(
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg",
+        ));
+        info.isrc = Some(String::from("GBARL9300135"));
+        info.position = 42000;
+
+        let encoded = info.encode().unwrap();
+
+        assert_eq!(Track::decode(&encoded).unwrap().info, info);
+    }
+
+    #[test]
+    fn rejects_sources_with_specific_data() {
+        let mut info = Track::decode(RICK_ROLL).unwrap().info;
+        info.source_name = String::from("http");
+
+        assert!(matches!(
+            info.encode(),
+            Err(TrackCodecError::UnsupportedSource(source)) if source == "http"
+        ));
+    }
+}
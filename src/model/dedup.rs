@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use super::player::Track;
+
+/// How tracks count as the same when deduplicating them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DedupStrategy {
+    /// Same ISRC, or same identifier on the same source, so one song found on several sources is kept once
+    #[default]
+    Isrc,
+    /// Same identifier on the same source
+    Identifier,
+    /// Same encoded track
+    Encoded,
+}
+
+/// Removes the tracks whose key was already seen, keeping the first track of each key in order
+pub fn dedup_tracks_by<K: Eq + Hash>(tracks: Vec<Track>, key: impl Fn(&Track) -> K) -> Vec<Track> {
+    let mut seen = HashSet::new();
+
+    tracks
+        .into_iter()
+        .filter(|track| seen.insert(key(track)))
+        .collect()
+}
+
+/// Removes the duplicated tracks under a strategy, keeping the first track of each in order
+/// # Tracks are compared with [`Track::same_as`], so a track is dropped if it's the same as one that was kept
+pub fn dedup_tracks(tracks: Vec<Track>, strategy: DedupStrategy) -> Vec<Track> {
+    let mut kept: Vec<Track> = Vec::with_capacity(tracks.len());
+
+    for track in tracks {
+        if !kept.iter().any(|other| other.same_as(&track, strategy)) {
+            kept.push(track);
+        }
+    }
+
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    use super::*;
+    use crate::model::player::TrackInfo;
+
+    fn track(source: &str, identifier: &str, isrc: Option<&str>) -> Track {
+        let mut info = TrackInfo::new(identifier, identifier, "Author", 1000, source);
+        info.isrc = isrc.map(String::from);
+
+        Track {
+            encoded: format!("{source}-{identifier}"),
+            info,
+            plugin_info: Value::Null,
+            user_data: None,
+        }
+    }
+
+    fn hash(track: &Track) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        track.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn same_as_follows_the_strategy() {
+        let youtube = track("youtube", "1", Some("GBARL9300135"));
+        let spotify = track("spotify", "2", Some("GBARL9300135"));
+        let reencoded = Track {
+            encoded: String::from("other"),
+            ..youtube.clone()
+        };
+
+        assert!(youtube.same_as(&spotify, DedupStrategy::Isrc));
+        assert!(!youtube.same_as(&spotify, DedupStrategy::Identifier));
+        assert!(!youtube.same_as(&spotify, DedupStrategy::Encoded));
+
+        assert!(youtube.same_as(&reencoded, DedupStrategy::Isrc));
+        assert!(youtube.same_as(&reencoded, DedupStrategy::Identifier));
+        assert!(!youtube.same_as(&reencoded, DedupStrategy::Encoded));
+
+        let without_isrc = track("youtube", "3", None);
+
+        assert!(!without_isrc.same_as(&track("spotify", "3", None), DedupStrategy::Isrc));
+    }
+
+    #[test]
+    fn equal_tracks_share_a_hash() {
+        let youtube = track("youtube", "1", Some("GBARL9300135"));
+        let mut with_data = youtube.clone();
+        with_data.user_data = Some(Value::Bool(true));
+
+        assert_eq!(youtube, youtube.clone());
+        assert_eq!(hash(&youtube), hash(&youtube.clone()));
+
+        // unequal tracks that are the same by identifier still share a hash
+        assert_ne!(youtube, with_data);
+        assert!(youtube.same_as(&with_data, DedupStrategy::Identifier));
+        assert_eq!(hash(&youtube), hash(&with_data));
+
+        let set: HashSet<Track> = [youtube.clone(), youtube.clone(), with_data].into();
+
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn dedup_keeps_the_first_of_each_track() {
+        let tracks = vec![
+            track("youtube", "1", Some("GBARL9300135")),
+            track("spotify", "2", Some("GBARL9300135")),
+            track("youtube", "1", None),
+            track("soundcloud", "3", None),
+        ];
+
+        let identifiers = |tracks: Vec<Track>| -> Vec<String> {
+            tracks
+                .into_iter()
+                .map(|track| track.info.identifier)
+                .collect()
+        };
+
+        assert_eq!(
+            identifiers(dedup_tracks(tracks.clone(), DedupStrategy::Isrc)),
+            ["1", "3"]
+        );
+        assert_eq!(
+            identifiers(dedup_tracks(tracks.clone(), DedupStrategy::Identifier)),
+            ["1", "2", "3"]
+        );
+        assert_eq!(
+            identifiers(dedup_tracks(tracks, DedupStrategy::Encoded)),
+            ["1", "2", "3"]
+        );
+    }
+}
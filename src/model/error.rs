@@ -8,7 +8,10 @@ pub enum LavalinkNodeError {
     #[error(transparent)]
     TungsteniteHttp(#[from] tokio_tungstenite::tungstenite::http::Error),
     #[error(transparent)]
-    InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
+    InvalidHeaderValue(#[from] tokio_tungstenite::tungstenite::http::header::InvalidHeaderValue),
+    #[cfg(feature = "reqwest")]
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     FlumeRecv(#[from] flume::RecvError),
     #[error(transparent)]
@@ -17,6 +20,27 @@ pub enum LavalinkNodeError {
     TokioOneshotChannelSend(String),
     #[error("Failed to receive data from node worker => {}", .0.to_string())]
     TokioOneshotChannelRecv(#[from] tokio::sync::oneshot::error::RecvError),
+    #[error("Failed to configure TLS for this node ({0})")]
+    Tls(String),
+    #[error("Node was shut down by its cancellation token")]
+    Cancelled,
+    #[error("Websocket handshake was redirected more than {0} time(s)")]
+    TooManyRedirects(usize),
+    #[error("Websocket handshake was redirected to an invalid location ({0})")]
+    InvalidRedirect(String),
+    #[error("Websocket of this node is not connected")]
+    NotConnected,
+    #[error("Websocket was closed by the node ({code}: {reason})")]
+    Closed { code: u16, reason: String },
+    #[error("Node rejected the credentials on the websocket handshake (HTTP {0})")]
+    InvalidCredentials(u16),
+    #[error("Node rejected the credentials and closed the websocket ({code}: {reason})")]
+    AuthenticationFailed { code: u16, reason: String },
+    #[error("Node runs lavalink {version}, which does not satisfy {required}")]
+    UnsupportedVersion { version: String, required: String },
+    #[cfg(feature = "debug-recorder")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
 /// List of errors that can throw from an instance of Lavalink Rest
@@ -26,27 +50,106 @@ pub enum LavalinkRestError {
     LavalinkNode(#[from] LavalinkNodeError),
     #[error(transparent)]
     SerdeParse(#[from] serde_json::Error),
+    #[cfg(feature = "simd-json")]
+    #[error(transparent)]
+    SimdParse(#[from] simd_json::Error),
+    #[cfg(feature = "reqwest")]
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
+    #[error(transparent)]
+    InvalidHeaderValue(#[from] tokio_tungstenite::tungstenite::http::header::InvalidHeaderValue),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error("HTTP transport failed to send the request ({0})")]
+    Transport(String),
     #[error("Response received is not ok ({})", .0.to_string())]
-    ResponseReceivedNotOk(reqwest::StatusCode),
+    ResponseReceivedNotOk(tokio_tungstenite::tungstenite::http::StatusCode),
     #[error("No Session Id present to create this request")]
     NoSessionId,
+    #[error("Session of this request no longer exists on the node")]
+    SessionNotFound,
     #[error("Unexpected none result on a function that should have a result")]
     NothingReturned,
 }
 
+/// List of errors that can throw from a queue or its store
+#[derive(ThisError, Debug)]
+pub enum QueueError {
+    #[error(transparent)]
+    SerdeParse(#[from] serde_json::Error),
+    #[cfg(feature = "redis")]
+    #[error(transparent)]
+    Redis(#[from] redis::RedisError),
+    #[cfg(feature = "sled")]
+    #[error(transparent)]
+    Sled(#[from] sled::Error),
+    #[error("Track is already in the queue")]
+    DuplicateTrack,
+    #[error("Queue has no track to play next")]
+    Empty,
+    #[error("Queue has no track that played before")]
+    HistoryEmpty,
+    #[error("Position {index} is out of bounds for a queue of {len} tracks")]
+    OutOfBounds { index: usize, len: usize },
+    #[error("Queue is full, it holds up to {0} tracks")]
+    Full(usize),
+}
+
+/// List of errors that can throw from the presets or their store
+#[derive(ThisError, Debug)]
+pub enum PresetError {
+    #[error(transparent)]
+    SerdeParse(#[from] serde_json::Error),
+    #[error("Preset store failed ({0})")]
+    Store(String),
+}
+
+/// List of errors that can throw while encoding or decoding a track locally
+#[derive(ThisError, Debug)]
+pub enum TrackCodecError {
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error("Encoded track ended before all of its data was read")]
+    UnexpectedEnd,
+    #[error("Encoded track has a string that is not valid modified UTF-8")]
+    InvalidString,
+    #[error("Encoded track uses an unsupported version ({0})")]
+    UnsupportedVersion(u8),
+    #[error("Tracks from this source can't be encoded locally ({0})")]
+    UnsupportedSource(String),
+}
+
 /// List of errors that can throw from an instance of Lavalink Player
 #[derive(ThisError, Debug)]
 pub enum LavalinkPlayerError {
     #[error(transparent)]
     LavalinkRest(#[from] LavalinkRestError),
     #[error(transparent)]
+    Queue(#[from] QueueError),
+    #[error(transparent)]
+    Preset(#[from] PresetError),
+    #[error(transparent)]
     FlumeRecv(#[from] flume::RecvError),
     #[error(transparent)]
     TokioRecv(#[from] tokio::sync::oneshot::error::RecvError),
+    #[error(transparent)]
+    TokioJoin(#[from] tokio::task::JoinError),
     #[error("Failed to send an event ({0})")]
     FlumeSend(String),
+    #[error("Volume {volume} is out of range, must be between 0 and {max}")]
+    InvalidVolume { volume: u32, max: u32 },
+    #[error("Low pass smoothing {0} is out of range, must be higher than 1.0")]
+    InvalidSmoothing(f64),
+    #[error("Filter {filter} is disabled on node {node}")]
+    FilterDisabledOnNode { filter: String, node: String },
+    #[error("Player is not playing a track")]
+    NothingPlaying,
+    #[error("Player has no voice connection to update")]
+    NoVoiceConnection,
+    #[error("Voice data is invalid ({0})")]
+    InvalidVoiceData(String),
+    #[error("No preset is registered with this name ({0})")]
+    PresetNotFound(String),
 }
 
 /// List of errors that can throw from an instance of Anchorage
@@ -58,10 +161,45 @@ pub enum AnchorageError {
     LavalinkPlayer(#[from] LavalinkPlayerError),
     #[error(transparent)]
     LavalinkRest(#[from] LavalinkRestError),
+    #[error(transparent)]
+    Queue(#[from] QueueError),
     #[error("Tried to create a new player when there is already an existing one")]
     CreateExistingPlayer,
     #[error("No nodes available to get")]
     NoNodesAvailable,
+    #[error("Node address is not a valid host and port ({0})")]
+    InvalidNodeAddress(String),
+    #[error("Client name must be in Name/Version format ({0})")]
+    InvalidClientName(String),
+    #[error("Node header is not a valid header name or value ({0})")]
+    InvalidNodeHeader(String),
+    #[error("Anchorage needs to be started first")]
+    NotStarted,
+    #[error("Anchorage was already started with another user id ({0}), got ({1})")]
+    UserIdMismatch(crate::model::id::UserId, crate::model::id::UserId),
+    #[error("A node with this name already exists ({0})")]
+    NodeAlreadyExists(String),
+    #[error("No node exists with this name ({0})")]
+    NodeNotFound(String),
+    #[error("No player exists for this guild ({0})")]
+    PlayerNotFound(crate::model::id::GuildId),
+    #[error("Node {0} is connected with another user id ({1}) than the node of the player ({2})")]
+    NodeUserIdMismatch(String, crate::model::id::UserId, crate::model::id::UserId),
+    #[cfg(feature = "dns")]
+    #[error(transparent)]
+    Dns(#[from] hickory_resolver::ResolveError),
+    #[cfg(any(feature = "http-discovery", feature = "watch-config"))]
+    #[error(transparent)]
+    SerdeParse(#[from] serde_json::Error),
+    #[cfg(feature = "watch-config")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "watch-config")]
+    #[error(transparent)]
+    Notify(#[from] notify::Error),
+    #[cfg(feature = "testcontainers")]
+    #[error(transparent)]
+    Testcontainers(#[from] testcontainers::TestcontainersError),
 }
 
 impl<T> From<flume::SendError<T>> for LavalinkPlayerError {
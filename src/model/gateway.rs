@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::id::{ChannelId, GuildId};
+
+/// Opcode of the voice state update gateway payload
+pub const VOICE_STATE_UPDATE_OP: u8 = 4;
+
+/// Voice state update sent through the Discord gateway to join, move or leave a voice channel
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoiceStateUpdate {
+    pub guild_id: GuildId,
+    /// Channel to join, none to leave the current one
+    pub channel_id: Option<ChannelId>,
+    pub self_mute: bool,
+    pub self_deaf: bool,
+}
+
+impl VoiceStateUpdate {
+    /// Joins or moves to a voice channel, deafened since the bot doesn't need to hear anyone
+    pub fn join(guild_id: impl Into<GuildId>, channel_id: impl Into<ChannelId>) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            channel_id: Some(channel_id.into()),
+            self_mute: false,
+            self_deaf: true,
+        }
+    }
+
+    /// Leaves the voice channel of a guild
+    pub fn leave(guild_id: impl Into<GuildId>) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            channel_id: None,
+            self_mute: false,
+            self_deaf: false,
+        }
+    }
+
+    /// Full gateway payload of this update, ready to be sent on the shard of its guild
+    pub fn payload(&self) -> Value {
+        json!({
+            "op": VOICE_STATE_UPDATE_OP,
+            "d": self,
+        })
+    }
+}
+
+/// Voice state update along with the shard it has to be sent on
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutedVoiceState {
+    pub shard: u64,
+    pub update: VoiceStateUpdate,
+}
@@ -0,0 +1,223 @@
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+macro_rules! discord_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            /// Creates a new id from its raw value
+            pub const fn new(id: u64) -> Self {
+                Self(id)
+            }
+
+            /// Gets the raw value of this id
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self(s.parse()?))
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(IdVisitor).map(Self)
+            }
+        }
+    };
+}
+
+discord_id!(
+    /// Id of a Discord guild, serialized as a string like lavalink does
+    GuildId
+);
+discord_id!(
+    /// Id of a Discord user, serialized as a string like lavalink does
+    UserId
+);
+discord_id!(
+    /// Id of a Discord channel, serialized as a string like lavalink does
+    ChannelId
+);
+
+/// Accepts an id either as a string or as a number
+///
+/// Lavalink sends ids as strings, but some v4 compatible servers send guild ids as numbers.
+/// Every id in the models goes through this, so events, player updates and players accept both
+struct IdVisitor;
+
+impl Visitor<'_> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a discord id as a string or an integer")
+    }
+
+    fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: Error>(self, value: i64) -> Result<Self::Value, E> {
+        u64::try_from(value).map_err(Error::custom)
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(Error::custom)
+    }
+}
+
+#[cfg(feature = "serenity")]
+mod serenity_interop {
+    use super::{ChannelId, GuildId, UserId};
+
+    impl From<serenity::model::id::GuildId> for GuildId {
+        fn from(id: serenity::model::id::GuildId) -> Self {
+            Self(id.get())
+        }
+    }
+
+    impl From<serenity::model::id::UserId> for UserId {
+        fn from(id: serenity::model::id::UserId) -> Self {
+            Self(id.get())
+        }
+    }
+
+    impl From<serenity::model::id::ChannelId> for ChannelId {
+        fn from(id: serenity::model::id::ChannelId) -> Self {
+            Self(id.get())
+        }
+    }
+}
+
+#[cfg(feature = "twilight")]
+mod twilight_interop {
+    use super::{ChannelId, GuildId, UserId};
+    use twilight_model::id::Id;
+    use twilight_model::id::marker::{ChannelMarker, GuildMarker, UserMarker};
+
+    impl From<Id<GuildMarker>> for GuildId {
+        fn from(id: Id<GuildMarker>) -> Self {
+            Self(id.get())
+        }
+    }
+
+    impl From<Id<UserMarker>> for UserId {
+        fn from(id: Id<UserMarker>) -> Self {
+            Self(id.get())
+        }
+    }
+
+    impl From<Id<ChannelMarker>> for ChannelId {
+        fn from(id: Id<ChannelMarker>) -> Self {
+            Self(id.get())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::GuildId;
+    use crate::model::node::PlayerUpdate;
+    use crate::model::player::{LavalinkPlayer, PlayerEvents};
+
+    const GUILD_ID: u64 = 423116740810244097;
+
+    /// Guild id the way lavalink sends it, and the way some v4 compatible servers do
+    fn guild_ids() -> [Value; 2] {
+        [json!(GUILD_ID.to_string()), json!(GUILD_ID)]
+    }
+
+    #[test]
+    fn player_update_accepts_both_guild_ids() {
+        for guild_id in guild_ids() {
+            let update: PlayerUpdate = serde_json::from_value(json!({
+                "guildId": guild_id,
+                "state": { "time": 1500, "position": 6000, "connected": true, "ping": 50 }
+            }))
+            .unwrap();
+
+            assert_eq!(update.guild_id, GuildId::new(GUILD_ID));
+        }
+    }
+
+    #[test]
+    fn player_accepts_both_guild_ids() {
+        for guild_id in guild_ids() {
+            let player: LavalinkPlayer = serde_json::from_value(json!({
+                "guildId": guild_id,
+                "track": null,
+                "volume": 100,
+                "paused": false,
+                "state": { "time": 1500, "position": 0, "connected": false, "ping": -1 },
+                "voice": {
+                    "token": "token",
+                    "endpoint": "endpoint",
+                    "sessionId": "session",
+                    "channelId": "564749582744027156"
+                },
+                "filters": {}
+            }))
+            .unwrap();
+
+            assert_eq!(player.guild_id, GuildId::new(GUILD_ID));
+        }
+    }
+
+    #[test]
+    fn player_event_accepts_both_guild_ids() {
+        for guild_id in guild_ids() {
+            let event: PlayerEvents = serde_json::from_value(json!({
+                "op": "event",
+                "type": "WebSocketClosedEvent",
+                "guildId": guild_id,
+                "code": 4006,
+                "reason": "Your session is no longer valid.",
+                "byRemote": true
+            }))
+            .unwrap();
+
+            let PlayerEvents::WebSocketClosedEvent(closed) = event else {
+                panic!("expected a websocket closed event");
+            };
+
+            assert_eq!(closed.guild_id, GuildId::new(GUILD_ID));
+        }
+    }
+}
@@ -1,29 +1,16 @@
-use serde::Deserialize;
-
 /// Contains various structure data for anchorage use
 pub mod anchorage;
+/// Contains the lavaplayer track format encoder and decoder
+pub mod codec;
+/// Contains the helpers that remove duplicated tracks
+pub mod dedup;
 /// Contains the errors the library is using
 pub mod error;
+/// Contains the Discord gateway payloads anchorage builds
+pub mod gateway;
+/// Contains the typed Discord ids
+pub mod id;
 /// Contains various structure data for lavalink node
 pub mod node;
 /// Contains various structure data for lavalink player
 pub mod player;
-
-fn str_to_u64<'de, T, D>(de: D) -> Result<T, D::Error>
-where
-    D: serde::Deserializer<'de>,
-    T: std::str::FromStr,
-    <T as std::str::FromStr>::Err: std::fmt::Display,
-{
-    String::deserialize(de)?
-        .parse()
-        .map_err(serde::de::Error::custom)
-}
-
-fn u64_to_str<T, D>(value: &T, serializer: D) -> Result<D::Ok, D::Error>
-where
-    T: std::fmt::Display,
-    D: serde::Serializer,
-{
-    serializer.serialize_str(&value.to_string())
-}
@@ -1,15 +1,17 @@
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
+use super::id::GuildId;
 use super::player::{LavalinkPlayerState, PlayerEvents};
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct FrameStats {
     pub sent: u64,
     pub nulled: u32,
     pub deficit: i32,
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Cpu {
     pub cores: u32,
@@ -17,7 +19,7 @@ pub struct Cpu {
     pub lavalink_load: f64,
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Memory {
     pub free: u64,
     pub used: u64,
@@ -35,12 +37,13 @@ pub struct Ready {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerUpdate {
-    pub guild_id: String,
+    pub guild_id: GuildId,
     pub state: LavalinkPlayerState,
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Stats {
     pub players: u32,
     pub playing_players: u32,
@@ -63,8 +66,15 @@ pub enum LavalinkMessage {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionInfo {
-    resuming: bool,
-    timeout: u32,
+    pub resuming: bool,
+    pub timeout: u32,
+}
+
+impl SessionInfo {
+    /// Creates a new session info, timeout is in seconds
+    pub fn new(resuming: bool, timeout: u32) -> Self {
+        Self { resuming, timeout }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -102,7 +112,7 @@ pub struct RoutePlanner {
     pub details: Option<RoutePlannerDetails>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeVersion {
     pub semver: String,
@@ -113,7 +123,7 @@ pub struct NodeVersion {
     pub build: Option<String>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeGit {
     pub branch: String,
@@ -128,15 +138,162 @@ pub struct NodePlugin {
     pub version: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Info of a node, construct it from [`Default`] outside of deserialization
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LavalinkInfo {
     pub version: NodeVersion,
     pub build_time: u64,
     pub git: NodeGit,
     pub jvm: String,
     pub lavaplayer: String,
-    pub source_managers: String,
+    #[serde(default)]
+    pub source_managers: Vec<String>,
+    #[serde(default)]
     pub filters: Vec<String>,
+    #[serde(default)]
     pub plugins: Vec<NodePlugin>,
 }
+
+/// What a node supports, taken from its info
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeCapabilities {
+    /// Semantic version of lavalink
+    pub version: String,
+    /// Filters that are enabled
+    pub filters: Vec<String>,
+    /// Source managers that are enabled
+    pub source_managers: Vec<String>,
+    /// Names of the loaded plugins
+    pub plugins: Vec<String>,
+}
+
+impl NodeCapabilities {
+    /// Checks if a filter is enabled, by its name in the filters payload (e.g. `channelMix`)
+    pub fn supports_filter(&self, name: &str) -> bool {
+        self.filters.iter().any(|filter| filter == name)
+    }
+
+    /// Checks if a source manager is enabled (e.g. `youtube`)
+    pub fn supports_source(&self, name: &str) -> bool {
+        self.source_managers.iter().any(|source| source == name)
+    }
+
+    /// Checks if a plugin is loaded
+    pub fn has_plugin(&self, name: &str) -> bool {
+        self.plugins.iter().any(|plugin| plugin == name)
+    }
+}
+
+impl From<&LavalinkInfo> for NodeCapabilities {
+    fn from(info: &LavalinkInfo) -> Self {
+        Self {
+            version: info.version.semver.clone(),
+            filters: info.filters.clone(),
+            source_managers: info.source_managers.clone(),
+            plugins: info
+                .plugins
+                .iter()
+                .map(|plugin| plugin.name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Connection state of a node
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeState {
+    /// Connecting for the first time, or after a disconnect
+    Connecting,
+    /// Websocket is connected
+    Connected,
+    /// Connection was lost, and is being established again
+    Reconnecting { attempt: u16 },
+    /// Websocket is not connected, and won't be until asked to
+    #[default]
+    Disconnected,
+    /// Node was destroyed, and can't be connected again
+    Destroyed,
+}
+
+/// Transition of a node from one connection state to another
+#[derive(Clone, Debug)]
+pub struct NodeStateChange {
+    /// Name of the node
+    pub name: String,
+    pub from: NodeState,
+    pub to: NodeState,
+    /// When the transition happened
+    pub at: SystemTime,
+    /// Error that caused the transition, if one did
+    pub error: Option<String>,
+}
+
+/// Traffic the websocket of a node received, to spot connections that silently stalled
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConnectionStats {
+    /// Bytes received per second, over the last second or more
+    pub bytes_per_second: f64,
+    /// Messages received per second, over the last second or more
+    pub messages_per_second: f64,
+    /// Bytes received since this node was created
+    pub bytes_received: u64,
+    /// Messages received since this node was created
+    pub messages_received: u64,
+    /// When the last message was received, none if none was
+    pub last_message_at: Option<SystemTime>,
+}
+
+/// Health alerts a node raises while running
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeEvent {
+    /// Frame statistics stayed past the degradation thresholds, meaning audio is likely stuttering
+    Degraded {
+        /// Name of the node
+        name: String,
+        /// Frame deficit of the last statistics payload
+        deficit: i32,
+        /// Nulled frames of the last statistics payload
+        nulled: u32,
+    },
+    /// Node was left out of ideal node selection for a while, after exceeding a quarantine threshold
+    Quarantined {
+        /// Name of the node
+        name: String,
+        /// How long until the node is probed again
+        duration: Duration,
+        /// Threshold the node exceeded
+        reason: String,
+    },
+    /// Node passed the probe after its quarantine, and is picked again
+    Recovered {
+        /// Name of the node
+        name: String,
+    },
+    /// Node runs a lavalink version its options don't allow, so it was refused
+    UnsupportedVersion {
+        /// Name of the node
+        name: String,
+        /// Version the node runs
+        version: String,
+        /// Versions the node was required to run
+        required: String,
+    },
+}
+
+impl Stats {
+    /// Penalties used for ideal node calculation, lower means less load
+    pub fn penalties(&self) -> f64 {
+        let mut penalties = self.players as f64;
+
+        penalties += f64::powf(1.05, 100.0 * self.cpu.system_load).round();
+
+        if let Some(frame_stats) = &self.frame_stats {
+            penalties += frame_stats.deficit as f64;
+            penalties += (frame_stats.nulled as f64) * 2.0;
+        }
+
+        penalties
+    }
+}
@@ -1,7 +1,13 @@
-use super::{str_to_u64, u64_to_str};
+use super::dedup::DedupStrategy;
+use super::id::{ChannelId, GuildId};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Highest player volume lavalink accepts
+pub const MAX_VOLUME: u32 = 1000;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -148,27 +154,27 @@ pub struct LavalinkVoice {
     pub token: String,
     pub endpoint: String,
     pub session_id: String,
-    #[serde(deserialize_with = "str_to_u64", serialize_with = "u64_to_str")]
-    pub channel_id: u64,
+    pub channel_id: ChannelId,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub connected: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ping: Option<i32>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct LavalinkPlayerState {
     pub time: u64,
-    pub position: u32,
+    /// Position of the current track in milliseconds
+    pub position: u64,
     pub connected: bool,
     pub ping: Option<i32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct LavalinkPlayer {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub track: Option<Track>,
     pub volume: u32,
     pub paused: bool,
@@ -177,15 +183,43 @@ pub struct LavalinkPlayer {
     pub filters: LavalinkFilters,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// State of a player that can be saved, then restored on any node
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSnapshot {
+    pub guild_id: GuildId,
+    /// Encoded track that was playing, if there was one
+    pub track: Option<String>,
+    /// Position of the track in milliseconds
+    pub position: u64,
+    pub paused: bool,
+    pub volume: u32,
+    pub filters: LavalinkFilters,
+    pub voice: LavalinkVoice,
+    /// If the bot joined deafened, so the restored player joins the same way
+    #[serde(default = "default_self_deaf")]
+    pub self_deaf: bool,
+    /// If the bot joined muted, so the restored player joins the same way
+    #[serde(default)]
+    pub self_mute: bool,
+}
+
+fn default_self_deaf() -> bool {
+    true
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TrackInfo {
     pub identifier: String,
     pub is_seekable: bool,
     pub author: String,
-    pub length: usize,
+    /// Length of the track in milliseconds
+    pub length: u64,
     pub is_stream: bool,
-    pub position: usize,
+    /// Position of the track in milliseconds
+    pub position: u64,
     pub title: String,
     pub uri: Option<String>,
     pub artwork_url: Option<String>,
@@ -193,18 +227,23 @@ pub struct TrackInfo {
     pub source_name: String,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Track lavalink loaded
+/// # Hashed by its source and identifier, so equal tracks and tracks that are the same by [`DedupStrategy::Identifier`] share a hash
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Track {
     pub encoded: String,
     pub info: TrackInfo,
     pub plugin_info: Value,
+    /// Custom data attached to this track, sent back by lavalink on events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_data: Option<Value>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Exception {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub message: Option<String>,
     pub severity: String,
     pub cause: String,
@@ -212,44 +251,70 @@ pub struct Exception {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TrackStart {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub track: Track,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TrackEnd {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub track: Track,
-    pub reason: String,
+    pub reason: TrackEndReason,
+}
+
+/// Why a track ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackEndReason {
+    /// Track finished playing
+    Finished,
+    /// Track failed to start, throwing an exception before providing any audio
+    LoadFailed,
+    /// Track was stopped
+    Stopped,
+    /// Track was replaced by another one
+    Replaced,
+    /// Player was cleaned up after being idle
+    Cleanup,
+    /// Reason this version doesn't know yet, treated like [`TrackEndReason::Stopped`]
+    #[serde(other)]
+    Unknown,
+}
+
+impl TrackEndReason {
+    /// If the next track should start, as lavalink recommends
+    pub fn may_start_next(self) -> bool {
+        matches!(self, TrackEndReason::Finished | TrackEndReason::LoadFailed)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TrackException {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub track: Track,
     pub exception: Exception,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct TrackStuck {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub track: Track,
-    pub threshold_ms: usize,
+    pub threshold_ms: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct WebSocketClosed {
-    #[serde(deserialize_with = "str_to_u64")]
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     pub code: usize,
     pub reason: String,
     pub by_remote: bool,
@@ -258,6 +323,7 @@ pub struct WebSocketClosed {
 #[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
+#[non_exhaustive]
 pub enum PlayerEvents {
     TrackStartEvent(TrackStart),
     TrackEndEvent(TrackEnd),
@@ -285,9 +351,11 @@ pub struct LavalinkPlayerOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identifier: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub position: Option<u32>,
+    /// Position to seek to in milliseconds
+    pub position: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub end_time: Option<u32>,
+    /// Time in milliseconds where the track stops
+    pub end_time: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -301,10 +369,331 @@ pub struct LavalinkPlayerOptions {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum EventType {
     Player(Box<PlayerEvents>),
-    Destroyed,
+    /// Player state lavalink kept after the node resumed its session
+    Resumed(Box<LavalinkPlayer>),
+    /// Player got disconnected from the voice channel (e.g. kicked, or the channel got deleted)
+    Disconnected {
+        by_remote: bool,
+    },
+    /// Playlist got queued through [`crate::player::Player::play_playlist`]
+    PlaylistQueued {
+        name: String,
+        /// Tracks that got queued after the one that started playing
+        queued: usize,
+        /// Tracks in the playlist
+        total: usize,
+    },
+    /// Player was moved to another node, its handles and events follow it there
+    Moved {
+        from: String,
+        to: String,
+    },
+    Destroyed(DestroyReason),
+}
+
+/// Why a player was destroyed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DestroyReason {
+    /// Node threw an unrecoverable error, gave up reconnecting, or lost the player
+    NodeFailed,
+    /// Node was disconnected or destroyed on request
+    NodeDisconnected,
+    /// Player was destroyed on request
+    Requested,
+    /// Player was destroyed for being idle
+    Idle,
+    /// Player was moved to another node
+    Moved,
+}
+
+impl Hash for Track {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.info.source_name.hash(state);
+        self.info.identifier.hash(state);
+    }
+}
+
+impl Track {
+    /// If both tracks are the same under a strategy, a looser comparison than equality
+    pub fn same_as(&self, other: &Track, by: DedupStrategy) -> bool {
+        match by {
+            DedupStrategy::Encoded => self.encoded == other.encoded,
+            DedupStrategy::Identifier => {
+                self.info.source_name == other.info.source_name
+                    && self.info.identifier == other.info.identifier
+            }
+            DedupStrategy::Isrc => {
+                matches!((&self.info.isrc, &other.info.isrc), (Some(a), Some(b)) if a == b)
+                    || self.same_as(other, DedupStrategy::Identifier)
+            }
+        }
+    }
+
+    /// Requester of this track, read from the `requester` key of its user data
+    pub fn requester(&self) -> Option<String> {
+        match self.user_data.as_ref()?.get("requester")? {
+            Value::String(requester) => Some(requester.clone()),
+            Value::Number(requester) => Some(requester.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl LavalinkPlayer {
+    /// Creates an idle player, the other fields are left to their defaults
+    pub fn new(guild_id: GuildId, voice: LavalinkVoice) -> Self {
+        Self {
+            guild_id,
+            track: None,
+            volume: 100,
+            paused: false,
+            state: LavalinkPlayerState::default(),
+            voice,
+            filters: LavalinkFilters::default(),
+        }
+    }
+}
+
+impl Exception {
+    /// Creates an exception of a track
+    pub fn new(
+        guild_id: GuildId,
+        message: Option<String>,
+        severity: Severity,
+        cause: impl Into<String>,
+    ) -> Self {
+        Self {
+            guild_id,
+            message,
+            // lavalink sends the severity in lowercase
+            severity: severity.to_string().to_lowercase(),
+            cause: cause.into(),
+        }
+    }
+}
+
+impl TrackStart {
+    /// Creates a track start event
+    pub fn new(guild_id: GuildId, track: Track) -> Self {
+        Self { guild_id, track }
+    }
+}
+
+impl TrackEnd {
+    /// Creates a track end event
+    pub fn new(guild_id: GuildId, track: Track, reason: TrackEndReason) -> Self {
+        Self {
+            guild_id,
+            track,
+            reason,
+        }
+    }
+}
+
+impl TrackException {
+    /// Creates a track exception event
+    pub fn new(guild_id: GuildId, track: Track, exception: Exception) -> Self {
+        Self {
+            guild_id,
+            track,
+            exception,
+        }
+    }
+}
+
+impl TrackStuck {
+    /// Creates a track stuck event
+    pub fn new(guild_id: GuildId, track: Track, threshold_ms: u64) -> Self {
+        Self {
+            guild_id,
+            track,
+            threshold_ms,
+        }
+    }
+}
+
+impl WebSocketClosed {
+    /// Creates a voice websocket closed event
+    pub fn new(guild_id: GuildId, code: usize, reason: impl Into<String>, by_remote: bool) -> Self {
+        Self {
+            guild_id,
+            code,
+            reason: reason.into(),
+            by_remote,
+        }
+    }
+}
+
+impl From<LavalinkPlayer> for PlayerSnapshot {
+    fn from(player: LavalinkPlayer) -> Self {
+        Self {
+            guild_id: player.guild_id,
+            track: player.track.map(|track| track.encoded),
+            position: player.state.position,
+            paused: player.paused,
+            volume: player.volume,
+            filters: player.filters,
+            voice: LavalinkVoice {
+                connected: None,
+                ping: None,
+                ..player.voice
+            },
+            // lavalink doesn't know these, the player that has them fills them in
+            self_deaf: true,
+            self_mute: false,
+        }
+    }
+}
+
+impl From<PlayerSnapshot> for LavalinkPlayerOptions {
+    fn from(snapshot: PlayerSnapshot) -> Self {
+        Self {
+            track: Some(UpdatePlayerTrack {
+                encoded: Some(snapshot.track.map(Value::String).unwrap_or(Value::Null)),
+                ..Default::default()
+            }),
+            position: Some(snapshot.position),
+            paused: Some(snapshot.paused),
+            volume: Some(snapshot.volume),
+            filters: Some(snapshot.filters),
+            voice: Some(snapshot.voice),
+            ..Default::default()
+        }
+    }
+}
+
+impl TrackPlaylist {
+    /// Index of the track the playlist should start from, the first one if none was selected
+    pub fn start_index(&self) -> usize {
+        usize::try_from(self.info.selected_track)
+            .ok()
+            .filter(|index| *index < self.tracks.len())
+            .unwrap_or(0)
+    }
+}
+
+impl PlayerEvents {
+    /// Guild Id of the player this event belongs to
+    pub fn guild_id(&self) -> GuildId {
+        match self {
+            PlayerEvents::TrackStartEvent(data) => data.guild_id,
+            PlayerEvents::TrackEndEvent(data) => data.guild_id,
+            PlayerEvents::TrackExceptionEvent(data) => data.guild_id,
+            PlayerEvents::TrackStuckEvent(data) => data.guild_id,
+            PlayerEvents::WebSocketClosedEvent(data) => data.guild_id,
+        }
+    }
+
+    /// Track this event is about, none for websocket closes
+    pub fn track(&self) -> Option<&Track> {
+        match self {
+            PlayerEvents::TrackStartEvent(data) => Some(&data.track),
+            PlayerEvents::TrackEndEvent(data) => Some(&data.track),
+            PlayerEvents::TrackExceptionEvent(data) => Some(&data.track),
+            PlayerEvents::TrackStuckEvent(data) => Some(&data.track),
+            PlayerEvents::WebSocketClosedEvent(_) => None,
+        }
+    }
+
+    pub(crate) fn track_mut(&mut self) -> Option<&mut Track> {
+        match self {
+            PlayerEvents::TrackStartEvent(data) => Some(&mut data.track),
+            PlayerEvents::TrackEndEvent(data) => Some(&mut data.track),
+            PlayerEvents::TrackExceptionEvent(data) => Some(&mut data.track),
+            PlayerEvents::TrackStuckEvent(data) => Some(&mut data.track),
+            PlayerEvents::WebSocketClosedEvent(_) => None,
+        }
+    }
+}
+
+impl From<&Track> for UpdatePlayerTrack {
+    /// Plays a track along with its user data, so lavalink sends it back on events
+    fn from(track: &Track) -> Self {
+        Self {
+            encoded: Some(Value::String(track.encoded.clone())),
+            user_data: track.user_data.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl LavalinkPlayerState {
+    /// Position of the current track as a duration
+    pub fn position(&self) -> Duration {
+        Duration::from_millis(self.position)
+    }
+
+    /// Position in milliseconds the track should be at now, if it kept playing since lavalink sent this state
+    pub fn interpolated_position(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or(self.time);
+
+        self.position.saturating_add(now.saturating_sub(self.time))
+    }
+}
+
+impl TrackInfo {
+    /// Creates the info of a track, the other fields are left to their defaults
+    pub fn new(
+        identifier: impl Into<String>,
+        title: impl Into<String>,
+        author: impl Into<String>,
+        length: u64,
+        source_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            identifier: identifier.into(),
+            title: title.into(),
+            author: author.into(),
+            length,
+            source_name: source_name.into(),
+            is_seekable: true,
+            ..Default::default()
+        }
+    }
+
+    /// Length of the track as a duration
+    pub fn length(&self) -> Duration {
+        Duration::from_millis(self.length)
+    }
+
+    /// Position of the track as a duration
+    pub fn position(&self) -> Duration {
+        Duration::from_millis(self.position)
+    }
 }
 
 impl LavalinkFilters {
+    /// Names of the filters that are set, as lavalink lists them in its info
+    pub fn names(&self) -> Vec<String> {
+        let builtin = [
+            ("volume", self.volume.is_some()),
+            ("equalizer", self.equalizer.is_some()),
+            ("karaoke", self.karaoke.is_some()),
+            ("timescale", self.timescale.is_some()),
+            ("tremolo", self.tremolo.is_some()),
+            ("vibrato", self.vibrato.is_some()),
+            ("rotation", self.rotation.is_some()),
+            ("distortion", self.distortion.is_some()),
+            ("channelMix", self.channel_mix.is_some()),
+            ("lowPass", self.low_pass.is_some()),
+        ];
+
+        let mut names: Vec<String> = builtin
+            .into_iter()
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| name.to_string())
+            .collect();
+
+        if let Some(Value::Object(plugins)) = &self.plugin_filters {
+            names.extend(plugins.keys().cloned());
+        }
+
+        names
+    }
+
     pub fn merge(&mut self, other: LavalinkFilters) {
         self.volume = other.volume.or(self.volume);
         self.equalizer = other.equalizer.or(self.equalizer.clone());
@@ -320,6 +709,77 @@ impl LavalinkFilters {
     }
 }
 
+impl Karaoke {
+    /// Frequency (in Hz) vocals are usually centered on
+    pub const VOCAL_BAND: f64 = 220.0;
+    /// Width (in Hz) of the band around [`Karaoke::VOCAL_BAND`] that gets removed
+    pub const VOCAL_WIDTH: f64 = 100.0;
+
+    /// Removes vocals fully, with the values lavaplayer uses by default
+    pub fn default_vocal_cut() -> Self {
+        Self::vocal_cut(1.0)
+    }
+
+    /// Removes vocals with a strength from 0.0 (untouched) to 1.0 (fully removed)
+    pub fn vocal_cut(level: f64) -> Self {
+        Self::vocal_cut_band(level, Self::VOCAL_BAND, Self::VOCAL_WIDTH)
+    }
+
+    /// Removes vocals around a custom band (in Hz), for voices higher or lower than usual
+    pub fn vocal_cut_band(level: f64, band: f64, width: f64) -> Self {
+        let level = level.clamp(0.0, 1.0);
+
+        Self {
+            level: Some(level),
+            mono_level: Some(level),
+            filter_band: Some(band),
+            filter_width: Some(width),
+        }
+    }
+}
+
+impl LowPass {
+    /// Muffles the audio with a strength from 0.0 (slightly muffled) to 1.0 (underwater)
+    pub fn muffled(strength: f64) -> Self {
+        let strength = strength.clamp(0.0, 1.0);
+
+        Self {
+            smoothing: Some(2.0 + strength * 48.0),
+        }
+    }
+}
+
+impl ChannelMix {
+    /// Mixes both channels into each other equally, so both play the same audio
+    pub fn mono() -> Self {
+        Self::stereo_width(0.0)
+    }
+
+    /// Plays the left channel on the right and the right channel on the left
+    pub fn swap_channels() -> Self {
+        Self {
+            left_to_left: Some(0.0),
+            left_to_right: Some(1.0),
+            right_to_left: Some(1.0),
+            right_to_right: Some(0.0),
+        }
+    }
+
+    /// Narrows the stereo image, from 0.0 (mono) to 1.0 (untouched stereo)
+    pub fn stereo_width(width: f64) -> Self {
+        let width = width.clamp(0.0, 1.0);
+        let same = (1.0 + width) / 2.0;
+        let cross = (1.0 - width) / 2.0;
+
+        Self {
+            left_to_left: Some(same),
+            left_to_right: Some(cross),
+            right_to_left: Some(cross),
+            right_to_right: Some(same),
+        }
+    }
+}
+
 impl Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
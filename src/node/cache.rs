@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::model::anchorage::ResolveCacheOptions;
+use crate::model::player::DataType;
+
+/// Counters of a resolve cache since it was created
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolveCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to go to a node
+    pub misses: u64,
+    /// Entries removed to make room for newer ones
+    pub evictions: u64,
+    /// Entries removed because they outlived the time to live
+    pub expirations: u64,
+    /// Entries currently cached
+    pub entries: usize,
+}
+
+/// A cached resolve result
+struct Entry {
+    data: DataType,
+    inserted_at: Instant,
+    used_at: u64,
+}
+
+/// Least recently used cache of resolve results, with a time to live
+pub struct ResolveCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl std::fmt::Debug for ResolveCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolveCache")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl ResolveCache {
+    /// Creates an empty cache
+    pub fn new(options: ResolveCacheOptions) -> Self {
+        Self {
+            capacity: options.capacity.max(1),
+            ttl: options.ttl,
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+        }
+    }
+
+    /// Gets the cached result of an identifier, if it's still fresh
+    pub fn get(&self, identifier: &str) -> Option<DataType> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let Some(entry) = entries.get_mut(identifier) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        if entry.inserted_at.elapsed() >= self.ttl {
+            entries.remove(identifier);
+            self.expirations.fetch_add(1, Ordering::Relaxed);
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        entry.used_at = self.clock.fetch_add(1, Ordering::Relaxed);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+
+        Some(entry.data.clone())
+    }
+
+    /// Caches the result of an identifier, evicting the least recently used entry if full
+    pub fn insert(&self, identifier: &str, data: DataType) {
+        let mut entries = self.entries.lock().unwrap();
+
+        if entries.len() >= self.capacity && !entries.contains_key(identifier) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used_at)
+                .map(|(key, _)| key.clone());
+
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        entries.insert(
+            identifier.to_string(),
+            Entry {
+                data,
+                inserted_at: Instant::now(),
+                used_at: self.clock.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+    }
+
+    /// Removes the cached result of an identifier
+    pub fn invalidate(&self, identifier: &str) {
+        self.entries.lock().unwrap().remove(identifier);
+    }
+
+    /// Removes every cached result
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Gets the counters of this cache
+    pub fn stats(&self) -> ResolveCacheStats {
+        ResolveCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+}
@@ -1,45 +1,103 @@
 use flume::{Receiver as FlumeReceiver, Sender as FlumeSender, unbounded};
 use scc::HashMap as ConcurrentHashMap;
-use std::collections::HashMap;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
 use std::result::Result;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
+use tokio::sync::broadcast::{Receiver as BroadcastReceiver, Sender as BroadcastSender};
 use tokio::sync::oneshot::{Sender as TokioOneshotSender, channel};
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
-use tokio_tungstenite::tungstenite::Error as TungsteniteError;
+use tokio::time::{Instant, sleep, sleep_until};
+use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::tungstenite::handshake::client::Request;
 use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use tokio_tungstenite::tungstenite::http::HeaderMap;
+use tokio_util::sync::CancellationToken;
 
+use crate::audit::AuditSink;
+use crate::model::anchorage::DegradationOptions;
+use crate::model::anchorage::NodeFailurePolicy;
 use crate::model::anchorage::NodeManagerOptions;
+use crate::model::anchorage::QuarantineOptions;
 use crate::model::anchorage::RestOptions;
-use crate::model::error::LavalinkNodeError;
-use crate::model::node::{LavalinkMessage, Stats};
-use crate::model::player::{EventType, PlayerEvents};
+use crate::model::anchorage::VoiceClosePolicy;
+use crate::model::error::{LavalinkNodeError, LavalinkRestError};
+use crate::model::id::{GuildId, UserId};
+use crate::model::node::{
+    ConnectionStats, LavalinkInfo, LavalinkMessage, NodeCapabilities, NodeEvent, NodeState,
+    NodeStateChange, SessionInfo, Stats,
+};
+use crate::model::player::{
+    DestroyReason, EventType, LavalinkPlayer, LavalinkPlayerOptions, LavalinkPlayerState,
+    LavalinkVoice, PlayerEvents, Track, TrackEndReason,
+};
+#[cfg(feature = "debug-recorder")]
+use crate::node::recorder::TrafficRecorder;
 use crate::node::rest::Rest;
-use crate::node::websocket::Connection;
+#[cfg(feature = "reqwest")]
+use crate::node::tls::rest_client;
+use crate::node::tls::websocket_connector;
+#[cfg(feature = "reqwest")]
+use crate::node::transport::ReqwestTransport;
+use crate::node::websocket::{Connection, Throughput};
+use crate::player::events::EventSender;
+use crate::player::{PlayerLocation, resume_position};
+use crate::preset::Presets;
+use crate::queue::Queues;
+
+/// Time without a Stats op from lavalink (about two intervals) before they are considered stale
+const STATS_STALE_AFTER: Duration = Duration::from_secs(120);
+/// Default amount of statistics payloads kept in the history of a node
+pub const STATS_HISTORY_SIZE: usize = 30;
+/// Default time a node stays connected before its reconnect attempts reset
+pub const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Resume timeout (in seconds) enabled around a reconnect on nodes that have resuming disabled
+const RECONNECT_RESUME_TIMEOUT: u32 = 30;
 
 pub enum WebsocketCommand {
     Connect(TokioOneshotSender<Result<(), LavalinkNodeError>>),
     Disconnect(TokioOneshotSender<()>),
     Destroy(TokioOneshotSender<()>),
+    Shutdown(TokioOneshotSender<()>),
     GetData(TokioOneshotSender<Result<NodeManagerData, LavalinkNodeError>>),
+    UpdateAuth(String, TokioOneshotSender<Result<(), LavalinkNodeError>>),
+    Reconnect(TokioOneshotSender<Result<(), LavalinkNodeError>>),
+    SendRaw(Value, TokioOneshotSender<Result<(), LavalinkNodeError>>),
+    SetResumeTimeout(Option<u32>, TokioOneshotSender<()>),
+    SetDraining(bool, TokioOneshotSender<()>),
 }
 
+#[derive(Clone, Debug)]
 pub struct NodeManagerData {
     /// Name of this node
     pub name: String,
     /// Authorization key for this node
     pub auth: String,
     /// UserId that this node will use
-    pub id: u64,
+    pub id: UserId,
     /// Base url for this node
     pub url: String,
     /// Penalties used for ideal node calculation
     pub penalties: f64,
     /// Status of this node
     pub statistics: Option<Stats>,
+    /// If the websocket stopped sending statistics, and they are being refreshed from REST instead
+    pub statistics_stale: bool,
+    /// Groups this node belongs to
+    pub groups: Vec<String>,
+    /// Connection state of this node
+    pub state: NodeState,
+    /// If this node is quarantined, and only picked when every other node is too
+    pub quarantined: bool,
+    /// Connection attempts made since this node was last stable
+    pub reconnects: u16,
+    /// Seconds lavalink keeps the session after a disconnect, none if resuming is disabled
+    pub resume_timeout: Option<u32>,
+    /// If this node is draining, and never picked for new players
+    pub draining: bool,
 }
 
 /// Internal websocket handler
@@ -49,29 +107,93 @@ pub struct NodeManager {
     /// Authentication key this node uses
     pub auth: String,
     /// User-Id of the bot connected to this node
-    pub id: u64,
+    pub id: UserId,
     /// Websocket URL that is being used to connect
     pub url: String,
+    /// Host and port of the websocket URL, used for the Host header
+    host: String,
+    /// Extra headers sent on the websocket handshake
+    headers: HeaderMap,
+    /// Authentication key shared with the rest of this node
+    rest_auth: Arc<RwLock<String>>,
     /// Load of this node
     pub penalties: f64,
     /// Statistics of this node
     pub statistics: Option<Stats>,
+    /// If the websocket stopped sending statistics, and they are being refreshed from REST instead
+    pub statistics_stale: bool,
+    /// Groups this node belongs to
+    pub groups: Vec<String>,
+    /// Connection state of this node
+    pub state: NodeState,
+    /// Rest interface for this node
+    pub rest: Rest,
     /// Current session id for this node
     pub session_id: Arc<RwLock<Option<String>>>,
     /// List of subscribers for this node player events, mapped by Guild Id and It's sender
-    pub event_senders: Arc<ConcurrentHashMap<u64, FlumeSender<EventType>>>,
+    pub event_senders: Arc<ConcurrentHashMap<GuildId, EventSender>>,
+    /// Broadcasts every statistics update this node receives
+    pub stats_sender: BroadcastSender<Stats>,
+    /// Broadcasts every connection state transition of this node
+    pub state_sender: BroadcastSender<NodeStateChange>,
+    /// Broadcasts the health alerts of this node
+    pub node_event_sender: BroadcastSender<NodeEvent>,
+    /// Last statistics this node received, oldest first
+    pub stats_history: Arc<Mutex<VecDeque<Stats>>>,
+    /// Copy of the data of this node, readable without a round-trip to this manager
+    pub data: Arc<Mutex<NodeManagerData>>,
+    /// Last voice data sent for each player, mapped by Guild Id
+    pub voices: Arc<ConcurrentHashMap<GuildId, LavalinkVoice>>,
+    /// Track each player is playing, mapped by Guild Id
+    pub now_playing: Arc<ConcurrentHashMap<GuildId, Track>>,
+    /// Queues of every player, advanced when a track ends
+    pub queues: Queues,
+    /// Node each player is on, shared with the players
+    locations: Arc<ConcurrentHashMap<GuildId, PlayerLocation>>,
+    /// Last state lavalink sent for each player, used to seek back after resuming
+    player_states: HashMap<GuildId, LavalinkPlayerState>,
     receivers: NodeReceivers,
     user_agent: String,
+    client_name: String,
     reconnect_tries: u16,
+    stats_history_size: usize,
+    degradation: DegradationOptions,
+    /// Statistics payloads in a row that exceeded a degradation threshold
+    degraded_streak: u32,
+    quarantine: Option<QuarantineOptions>,
+    /// When the quarantine of this node ends, and it gets probed again
+    quarantined_until: Option<Instant>,
+    /// Quarantines in a row, without recovering in between
+    quarantines: u32,
+    /// Failed connection attempts in a row
+    failures: u32,
+    /// Error that will be attached to the next state transition
+    state_error: Option<String>,
     connection: Connection,
     destroyed: bool,
+    /// If this node was shut down, and its players are kept on lavalink
+    shut_down: bool,
     reconnects: u16,
+    /// Time the websocket stays connected before the reconnect attempts reset
+    stable_after: Duration,
+    /// Lavalink versions this node has to run, any if none
+    min_version: Option<VersionReq>,
+    /// When the websocket last connected, none while it's not connected
+    connected_at: Option<Instant>,
+    statistics_received_at: Instant,
+    resume_timeout: Option<u32>,
+    draining: bool,
+    voice_close_policy: VoiceClosePolicy,
+    failure_policy: NodeFailurePolicy,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    cancellation_token: CancellationToken,
 }
 
 /// Wrapper around the websocket and command receivers for ease of usage
 pub struct NodeReceivers {
-    websocket: FlumeReceiver<Result<Option<LavalinkMessage>, TungsteniteError>>,
+    websocket: FlumeReceiver<Result<Option<LavalinkMessage>, LavalinkNodeError>>,
     command: FlumeReceiver<WebsocketCommand>,
+    session_invalidated: FlumeReceiver<()>,
 }
 
 impl From<&NodeManager> for NodeManagerData {
@@ -82,7 +204,14 @@ impl From<&NodeManager> for NodeManagerData {
             id: value.id,
             url: value.url.clone(),
             penalties: value.penalties,
-            statistics: value.statistics.clone(),
+            statistics: value.statistics,
+            statistics_stale: value.statistics_stale,
+            groups: value.groups.clone(),
+            state: value.state,
+            quarantined: value.quarantined_until.is_some(),
+            reconnects: value.reconnects,
+            resume_timeout: value.resume_timeout,
+            draining: value.draining,
         }
     }
 }
@@ -92,28 +221,127 @@ impl NodeManager {
     pub fn new(
         options: &NodeManagerOptions,
         commands_receiver: FlumeReceiver<WebsocketCommand>,
-    ) -> Self {
-        let (websocket_connection, message_receiver) = Connection::new();
+    ) -> Result<Self, LavalinkNodeError> {
+        let connector = options.tls.map(websocket_connector).transpose()?;
+
+        #[allow(unused_mut)]
+        let (mut websocket_connection, message_receiver) = Connection::new(
+            options.ws_transport.clone(),
+            connector,
+            options.cancellation_token.clone(),
+        );
+
+        #[cfg(feature = "debug-recorder")]
+        let recorder = options
+            .record_traffic
+            .map(|path| TrafficRecorder::create(options.name, path))
+            .transpose()?
+            .map(Arc::new);
+
+        #[cfg(feature = "debug-recorder")]
+        if let Some(recorder) = &recorder {
+            websocket_connection.record(recorder.clone());
+        }
+
+        // custom transports handle TLS themselves
+        let transport = match options.tls {
+            #[cfg(feature = "reqwest")]
+            Some(tls) if !options.custom_transport => {
+                Arc::new(ReqwestTransport::new(rest_client(tls, options.http)?))
+            }
+            _ => options.transport.clone(),
+        };
+
+        let rest_auth = Arc::new(RwLock::new(options.auth.to_string()));
+        let session_id = Arc::new(RwLock::new(options.session_id.clone()));
+        let (session_invalidated_sender, session_invalidated_receiver) = unbounded::<()>();
+
+        let rest = Rest::new(RestOptions {
+            transport,
+            url: options.rest_url.to_string(),
+            headers: options.headers.clone(),
+            auth: rest_auth.clone(),
+            user_agent: options.user_agent,
+            session_id: session_id.clone(),
+            session_invalidated: Some(session_invalidated_sender),
+            resolve_cache: options.resolve_cache.clone(),
+            scheduler: options.rest_scheduler.clone(),
+            audit_sink: options.audit_sink.clone(),
+            #[cfg(feature = "debug-recorder")]
+            recorder,
+        });
 
-        Self {
+        Ok(Self {
             name: options.name.to_string(),
             auth: options.auth.to_string(),
             id: options.id,
-            url: format!("ws://{}:{}/v4/websocket", options.host, options.port),
+            url: options.websocket_url.to_string(),
+            host: options.websocket_url.authority().to_string(),
+            headers: options.headers.clone(),
+            rest_auth,
             penalties: 0.0,
             statistics: None,
-            session_id: Arc::new(RwLock::new(None)),
+            statistics_stale: false,
+            statistics_received_at: Instant::now(),
+            groups: options.groups.clone(),
+            state: NodeState::Disconnected,
+            rest,
+            session_id,
             event_senders: Arc::new(ConcurrentHashMap::new()),
+            stats_sender: BroadcastSender::new(16),
+            state_sender: BroadcastSender::new(16),
+            node_event_sender: BroadcastSender::new(16),
+            stats_history: Arc::new(Mutex::new(VecDeque::with_capacity(options.stats_history))),
+            voices: Arc::new(ConcurrentHashMap::new()),
+            now_playing: Arc::new(ConcurrentHashMap::new()),
+            queues: options.queues.clone(),
+            locations: options.locations.clone(),
+            player_states: HashMap::new(),
             receivers: NodeReceivers {
                 websocket: message_receiver,
                 command: commands_receiver,
+                session_invalidated: session_invalidated_receiver,
             },
             user_agent: options.user_agent.to_string(),
+            client_name: options.client_name.to_string(),
             reconnect_tries: options.reconnect_tries,
+            stats_history_size: options.stats_history,
+            degradation: options.degradation.clone(),
+            degraded_streak: 0,
+            quarantine: options.quarantine.clone(),
+            quarantined_until: None,
+            quarantines: 0,
+            failures: 0,
+            state_error: None,
             connection: websocket_connection,
             destroyed: false,
+            shut_down: false,
             reconnects: 0,
-        }
+            stable_after: options.stable_after,
+            min_version: options.min_version.clone(),
+            connected_at: None,
+            resume_timeout: options.resume_timeout,
+            draining: false,
+            voice_close_policy: options.voice_close_policy.clone(),
+            failure_policy: options.failure_policy,
+            audit_sink: options.audit_sink.clone(),
+            cancellation_token: options.cancellation_token.clone(),
+            data: Arc::new(Mutex::new(NodeManagerData {
+                name: options.name.to_string(),
+                auth: options.auth.to_string(),
+                id: options.id,
+                url: options.websocket_url.to_string(),
+                penalties: 0.0,
+                statistics: None,
+                statistics_stale: false,
+                groups: options.groups.clone(),
+                state: NodeState::Disconnected,
+                quarantined: false,
+                reconnects: 0,
+                resume_timeout: options.resume_timeout,
+                draining: false,
+            })),
+        })
     }
 
     /// Starts this manager to listen for commands and messages
@@ -121,8 +349,17 @@ impl NodeManager {
     pub async fn start(&mut self) -> Result<(), LavalinkNodeError> {
         let result = self.handle().await;
 
-        // check players and handle accordingly
-        self.send_players_destroy().await;
+        let reason = match result {
+            Ok(_) => DestroyReason::NodeDisconnected,
+            Err(_) => DestroyReason::NodeFailed,
+        };
+
+        // players of a failed node are kept if the policy orphans or migrates them, and players of a shut down node are kept by lavalink
+        if !self.shut_down
+            && (result.is_ok() || self.failure_policy == NodeFailurePolicy::DestroyPlayers)
+        {
+            self.send_players_destroy(reason).await;
+        }
 
         result
     }
@@ -137,6 +374,16 @@ impl NodeManager {
                 Ok(command) = self.receivers.command.recv_async() => {
                     self.handle_command(command).await?;
                 }
+                Ok(_) = self.receivers.session_invalidated.recv_async() => {
+                    self.refresh_session().await?;
+                }
+                _ = self.cancellation_token.cancelled() => {
+                    tracing::info!("Lavalink Node {} cancelled. Shutting down...", self.name);
+                    self.destroy().await;
+                }
+                _ = sleep_until(self.statistics_received_at + STATS_STALE_AFTER), if self.connection.available() => {
+                    self.refresh_stale_statistics().await;
+                }
                 else => {
                     tracing::debug!("Lavalink Node {} stopped on listening for websocket messages & commands", self.name);
                     break;
@@ -148,15 +395,121 @@ impl NodeManager {
     }
 
     /// Send destroy event on all players in this node, then clears the events cache
-    async fn send_players_destroy(&mut self) {
+    async fn send_players_destroy(&mut self, reason: DestroyReason) {
+        let mut senders = vec![];
+
         self.event_senders
             .iter_async(|_, sender| {
-                sender.send(EventType::Destroyed).ok();
-                false
+                senders.push(sender.clone());
+                true
             })
             .await;
 
+        // a full bounded channel must not drop the destroy event, nor stall the node until it has room
+        tokio::spawn(async move {
+            for sender in senders {
+                sender.send(EventType::Destroyed(reason.clone())).await;
+            }
+        });
+
         self.event_senders.clear_async().await;
+        self.voices.clear_async().await;
+        self.player_states.clear();
+    }
+
+    /// Reconciles the players lavalink kept after a resume with the subscribers of this node
+    #[tracing::instrument(skip(self))]
+    async fn resync_players(&mut self) {
+        let players = match self.rest.get_players().await {
+            Ok(players) => players,
+            Err(error) => {
+                tracing::warn!(
+                    "Lavalink Node {} failed to fetch players after resuming => {:?}",
+                    self.name,
+                    error
+                );
+                return;
+            }
+        };
+
+        let mut missing = vec![];
+
+        self.event_senders
+            .iter_async(|guild_id, _| {
+                if !players.iter().any(|player| player.guild_id == *guild_id) {
+                    missing.push(*guild_id);
+                }
+                true
+            })
+            .await;
+
+        for guild_id in missing {
+            if let Some((_, sender)) = self.event_senders.remove_async(&guild_id).await {
+                sender
+                    .send(EventType::Destroyed(DestroyReason::NodeFailed))
+                    .await;
+            }
+        }
+
+        self.player_states
+            .retain(|guild_id, _| players.iter().any(|player| player.guild_id == *guild_id));
+
+        for player in players {
+            let Some(sender) = self
+                .event_senders
+                .read_async(&player.guild_id, |_, sender| sender.clone())
+                .await
+            else {
+                continue;
+            };
+
+            self.restore_position(&player).await;
+
+            sender.send(EventType::Resumed(Box::new(player))).await;
+        }
+    }
+
+    /// Seeks a resumed player back to where it was before the node disconnected, if the player asked for it
+    async fn restore_position(&self, player: &LavalinkPlayer) {
+        let Some(grace) = self
+            .locations
+            .read_async(&player.guild_id, |_, location| location.resume_grace())
+            .await
+            .flatten()
+        else {
+            return;
+        };
+
+        let Some(position) = self
+            .player_states
+            .get(&player.guild_id)
+            .and_then(|state| resume_position(player, state, grace))
+        else {
+            return;
+        };
+
+        // lavalink kept playing while the node was away, nothing to restore
+        if player.state.position >= position {
+            return;
+        }
+
+        let options = LavalinkPlayerOptions {
+            position: Some(position),
+            ..Default::default()
+        };
+
+        if let Err(error) = self
+            .rest
+            .update_player(player.guild_id, false, options)
+            .await
+        {
+            tracing::warn!(
+                "Lavalink Node {} failed to restore the position of the player of guild {} => {:?}",
+                self.name,
+                player.guild_id,
+                error
+            );
+        }
     }
 
     /// Handles commands received from interface struct
@@ -173,10 +526,39 @@ impl NodeManager {
                 self.destroy().await;
                 sender.send(()).ok();
             }
+            WebsocketCommand::Shutdown(sender) => {
+                self.shutdown().await;
+                sender.send(()).ok();
+            }
             WebsocketCommand::GetData(sender) => {
                 let me = &*self;
                 sender.send(Ok(me.into())).ok();
             }
+            WebsocketCommand::UpdateAuth(auth, sender) => {
+                sender.send(self.update_auth(auth).await).ok();
+            }
+            WebsocketCommand::Reconnect(sender) => {
+                sender.send(self.reconnect().await).ok();
+            }
+            WebsocketCommand::SendRaw(message, sender) => {
+                let result = self
+                    .connection
+                    .send(Message::Text(message.to_string().into()))
+                    .await;
+
+                sender.send(result).ok();
+            }
+            WebsocketCommand::SetResumeTimeout(timeout, sender) => {
+                // reconnects now keep or drop players depending on this, and enable resuming again on ready
+                self.resume_timeout = timeout;
+                self.sync_data();
+                sender.send(()).ok();
+            }
+            WebsocketCommand::SetDraining(draining, sender) => {
+                self.draining = draining;
+                self.sync_data();
+                sender.send(()).ok();
+            }
         }
 
         Ok(())
@@ -186,11 +568,30 @@ impl NodeManager {
     #[tracing::instrument(skip(self))]
     async fn handle_message(
         &mut self,
-        result: Result<Option<LavalinkMessage>, TungsteniteError>,
+        result: Result<Option<LavalinkMessage>, LavalinkNodeError>,
     ) -> Result<(), LavalinkNodeError> {
-        let Ok(option) = result else {
-            self.connect().await?;
-            return Ok(());
+        let option = match result {
+            Ok(option) => option,
+            Err(error @ LavalinkNodeError::AuthenticationFailed { .. }) => {
+                tracing::error!(
+                    "Lavalink Node {} rejected the credentials, not reconnecting => {}",
+                    self.name,
+                    error
+                );
+
+                // retrying with the same credentials would only get closed again
+                self.state_error = Some(error.to_string());
+                self.set_state(NodeState::Disconnected);
+
+                return Err(error);
+            }
+            Err(error) => {
+                self.state_error = Some(error.to_string());
+
+                // players are held until the ready tells if the session resumed, or until the node gives up and its failure policy takes them
+                self.connect().await?;
+                return Ok(());
+            }
         };
 
         let Some(message) = option else {
@@ -199,16 +600,12 @@ impl NodeManager {
 
         tracing::debug!("Lavalink Node {} received a message!", self.name);
 
+        if let Some(sink) = &self.audit_sink {
+            sink.on_ws_message(&self.name, &message);
+        }
+
         match message {
             LavalinkMessage::Ready(data) => {
-                {
-                    let _ = self
-                        .session_id
-                        .write()
-                        .await
-                        .insert(data.session_id.clone());
-                }
-
                 tracing::info!(
                     "Lavalink Node {} is now ready! [Resumed: {}] [Session Id: {}]",
                     self.name,
@@ -216,43 +613,421 @@ impl NodeManager {
                     data.session_id
                 );
 
-                Ok(())
-            }
-            LavalinkMessage::Stats(data) => {
-                let mut penalties: f64 = 0.0;
+                {
+                    let _ = self.session_id.write().await.insert(data.session_id);
+                }
 
-                let _ = self.statistics.insert(data.clone());
+                if let Some(timeout) = self.resume_timeout
+                    && let Err(error) = self
+                        .rest
+                        .update_session(SessionInfo::new(true, timeout))
+                        .await
+                {
+                    tracing::warn!(
+                        "Lavalink Node {} failed to enable resuming => {:?}",
+                        self.name,
+                        error
+                    );
+                }
 
-                penalties += data.players as f64;
-                penalties += f64::powf(1.05, 100.0 * data.cpu.system_load).round();
+                if data.resumed {
+                    // resuming was only enabled to keep the players through a reconnect
+                    if self.resume_timeout.is_none()
+                        && let Err(error) =
+                            self.rest.update_session(SessionInfo::new(false, 0)).await
+                    {
+                        tracing::warn!(
+                            "Lavalink Node {} failed to disable resuming again => {:?}",
+                            self.name,
+                            error
+                        );
+                    }
+
+                    self.resync_players().await;
+                } else {
+                    // the players held through the reconnect lived on a session that couldn't be resumed
+                    if !self.event_senders.is_empty() {
+                        tracing::warn!(
+                            "Lavalink Node {} could not resume its session, destroying its players",
+                            self.name
+                        );
+
+                        self.send_players_destroy(DestroyReason::NodeFailed).await;
+                    }
+
+                    // the server could have restarted with other plugins or filters
+                    self.rest.clear_info_cache().await;
+                }
 
-                if data.frame_stats.is_some() {
-                    penalties += data.frame_stats.clone().unwrap().deficit as f64;
-                    penalties += (data.frame_stats.clone().unwrap().nulled as f64) * 2.0;
+                Ok(())
+            }
+            LavalinkMessage::PlayerUpdate(data) => {
+                if self
+                    .event_senders
+                    .read_async(&data.guild_id, |_, _| ())
+                    .await
+                    .is_some()
+                {
+                    self.player_states.insert(data.guild_id, data.state);
                 }
 
-                self.penalties = penalties;
+                Ok(())
+            }
+            LavalinkMessage::Stats(data) => {
+                self.statistics_stale = false;
+
+                self.update_statistics(data);
 
                 Ok(())
             }
-            LavalinkMessage::Event(data) => {
-                let guild_id = match data.as_ref() {
-                    PlayerEvents::TrackStartEvent(data) => &data.guild_id,
-                    PlayerEvents::TrackEndEvent(data) => &data.guild_id,
-                    PlayerEvents::TrackExceptionEvent(data) => &data.guild_id,
-                    PlayerEvents::TrackStuckEvent(data) => &data.guild_id,
-                    PlayerEvents::WebSocketClosedEvent(data) => &data.guild_id,
+            LavalinkMessage::Event(mut data) => {
+                let guild_id = data.guild_id();
+
+                let Some(sender) = self
+                    .event_senders
+                    .read_async(&guild_id, |_, sender| sender.clone())
+                    .await
+                else {
+                    return Ok(());
                 };
 
-                let Some(sender) = self.event_senders.get_async(guild_id).await else {
-                    return Ok(());
+                // only the close code and origin are needed once the event is sent away
+                let closed = match data.as_ref() {
+                    PlayerEvents::WebSocketClosedEvent(closed) => {
+                        Some((closed.code, closed.by_remote))
+                    }
+                    _ => None,
+                };
+
+                let ended = match data.as_ref() {
+                    PlayerEvents::TrackEndEvent(end) => Some(end.reason),
+                    _ => None,
                 };
 
-                sender.send_async(EventType::Player(data)).await.ok();
+                self.attach_user_data(guild_id, &mut data).await;
+                self.update_now_playing(guild_id, &data).await;
+
+                sender.send(EventType::Player(data)).await;
+
+                if let Some((code, by_remote)) = closed {
+                    self.handle_voice_close(guild_id, code, by_remote, &sender)
+                        .await;
+                }
+
+                if let Some(reason) = ended {
+                    self.advance_queue(guild_id, reason);
+                }
 
                 Ok(())
             }
-            _ => Ok(()),
+        }
+    }
+
+    /// Fills the user data of the track of an event from the cached queue, when lavalink didn't send it back
+    ///
+    /// Only the cached queues are read, so a slow store never holds up the events of this node
+    async fn attach_user_data(&self, guild_id: GuildId, event: &mut PlayerEvents) {
+        let Some(track) = event.track_mut() else {
+            return;
+        };
+
+        if track.user_data.is_some() {
+            return;
+        }
+
+        if let Some(current) = self.queues.cached_current(guild_id).await
+            && current.encoded == track.encoded
+        {
+            track.user_data = current.user_data;
+        }
+    }
+
+    /// Remembers the track each player is playing, so it can be read without a round-trip
+    async fn update_now_playing(&self, guild_id: GuildId, event: &PlayerEvents) {
+        match event {
+            PlayerEvents::TrackStartEvent(start) => {
+                self.now_playing
+                    .upsert_async(guild_id, start.track.clone())
+                    .await;
+            }
+            PlayerEvents::TrackEndEvent(end) => {
+                // the next track could have started already
+                self.now_playing
+                    .remove_if_async(&guild_id, |track| track.encoded == end.track.encoded)
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Plays what the queue picks after a track ended, like the next queued track or a retry of a failed one
+    fn advance_queue(&self, guild_id: GuildId, reason: TrackEndReason) {
+        let queues = self.queues.clone();
+        let rest = self.rest.clone();
+        let name = self.name.clone();
+
+        // a slow queue store would hold up every other player of this node, so advance it on its own task
+        tokio::spawn(async move {
+            let track = match queues.track_ended(guild_id, reason).await {
+                Ok(Some(track)) => track,
+                Ok(None) => return,
+                Err(error) => {
+                    tracing::warn!(
+                        "Lavalink Node {} failed to advance the queue of guild {} => {:?}",
+                        name,
+                        guild_id,
+                        error
+                    );
+                    return;
+                }
+            };
+
+            let options = LavalinkPlayerOptions {
+                track: Some((&track).into()),
+                ..Default::default()
+            };
+
+            if let Err(error) = rest.update_player(guild_id, false, options).await {
+                tracing::warn!(
+                    "Lavalink Node {} failed to play the next track for guild {} => {:?}",
+                    name,
+                    guild_id,
+                    error
+                );
+            }
+        });
+    }
+
+    /// Applies the voice close policy on a voice websocket close reported by lavalink
+    async fn handle_voice_close(
+        &self,
+        guild_id: GuildId,
+        code: usize,
+        by_remote: bool,
+        sender: &EventSender,
+    ) {
+        if self.voice_close_policy.disconnect_codes.contains(&code) {
+            sender.send(EventType::Disconnected { by_remote }).await;
+
+            return;
+        }
+
+        if !self.voice_close_policy.resend_codes.contains(&code) {
+            return;
+        }
+
+        let Some(voice) = self
+            .voices
+            .read_async(&guild_id, |_, voice| voice.clone())
+            .await
+        else {
+            return;
+        };
+
+        tracing::debug!(
+            "Lavalink Node {} resending voice data for guild {} [Close Code: {}]",
+            self.name,
+            guild_id,
+            code
+        );
+
+        let rest = self.rest.clone();
+        let name = self.name.clone();
+
+        // resending voice data can take a while on a busy node, the events after the close shouldn't wait for it
+        tokio::spawn(async move {
+            let options = LavalinkPlayerOptions {
+                voice: Some(voice),
+                ..Default::default()
+            };
+
+            if let Err(error) = rest.update_player(guild_id, false, options).await {
+                tracing::warn!(
+                    "Lavalink Node {} failed to resend voice data for guild {} => {:?}",
+                    name,
+                    guild_id,
+                    error
+                );
+            }
+        });
+    }
+
+    /// Saves the statistics of this node, then recalculates its penalties
+    fn update_statistics(&mut self, data: Stats) {
+        self.penalties = data.penalties();
+        self.statistics = Some(data);
+        self.statistics_received_at = Instant::now();
+
+        if self.stats_history_size > 0 {
+            let mut history = self.stats_history.lock().unwrap();
+
+            if history.len() >= self.stats_history_size {
+                history.pop_front();
+            }
+
+            history.push_back(data);
+        }
+
+        self.sync_data();
+
+        self.check_degradation(data);
+        self.check_quarantine();
+
+        // no subscribers is not an error
+        self.stats_sender.send(data).ok();
+    }
+
+    /// Alerts once the frame statistics stayed past the degradation thresholds for enough payloads in a row
+    fn check_degradation(&mut self, data: Stats) {
+        let frames = data.frame_stats.unwrap_or_default();
+
+        if frames.deficit <= self.degradation.deficit && frames.nulled <= self.degradation.nulled {
+            self.degraded_streak = 0;
+            return;
+        }
+
+        self.degraded_streak += 1;
+
+        // alerts once per streak instead of on every payload past it
+        if self.degraded_streak != self.degradation.consecutive.max(1) {
+            return;
+        }
+
+        tracing::warn!(
+            "Lavalink Node {} is degraded (deficit: {}, nulled: {})",
+            self.name,
+            frames.deficit,
+            frames.nulled
+        );
+
+        // no subscribers is not an error
+        self.node_event_sender
+            .send(NodeEvent::Degraded {
+                name: self.name.clone(),
+                deficit: frames.deficit,
+                nulled: frames.nulled,
+            })
+            .ok();
+    }
+
+    /// Probes the health of this node on each statistics payload, quarantining or recovering it
+    fn check_quarantine(&mut self) {
+        let Some(options) = &self.quarantine else {
+            return;
+        };
+
+        if let Some(until) = self.quarantined_until
+            && Instant::now() < until
+        {
+            return;
+        }
+
+        if self.penalties > options.max_penalties {
+            let reason = format!(
+                "penalties of {:.2} exceeded {:.2}",
+                self.penalties, options.max_penalties
+            );
+
+            self.quarantine_node(reason);
+        } else if self.quarantined_until.is_some() {
+            self.recover();
+        }
+    }
+
+    /// Leaves this node out of ideal node selection, for longer on each quarantine in a row
+    fn quarantine_node(&mut self, reason: String) {
+        let Some(options) = &self.quarantine else {
+            return;
+        };
+
+        let duration = options
+            .duration
+            .saturating_mul(2u32.saturating_pow(self.quarantines))
+            .min(options.max_duration);
+
+        self.quarantines = self.quarantines.saturating_add(1);
+        self.quarantined_until = Some(Instant::now() + duration);
+        self.sync_data();
+
+        tracing::warn!(
+            "Lavalink Node {} is quarantined for {} second(s), {}",
+            self.name,
+            duration.as_secs(),
+            reason
+        );
+
+        // no subscribers is not an error
+        self.node_event_sender
+            .send(NodeEvent::Quarantined {
+                name: self.name.clone(),
+                duration,
+                reason,
+            })
+            .ok();
+    }
+
+    /// Puts this node back into ideal node selection
+    fn recover(&mut self) {
+        self.quarantined_until = None;
+        self.quarantines = 0;
+        self.sync_data();
+
+        tracing::info!("Lavalink Node {} recovered from its quarantine", self.name);
+
+        // no subscribers is not an error
+        self.node_event_sender
+            .send(NodeEvent::Recovered {
+                name: self.name.clone(),
+            })
+            .ok();
+    }
+
+    /// Updates the connection state of this node
+    fn set_state(&mut self, state: NodeState) {
+        let change = NodeStateChange {
+            name: self.name.clone(),
+            from: self.state,
+            to: state,
+            at: SystemTime::now(),
+            error: self.state_error.take(),
+        };
+
+        self.state = state;
+        self.sync_data();
+
+        // no subscribers is not an error
+        self.state_sender.send(change).ok();
+    }
+
+    /// Mirrors the current data of this manager to the node handles
+    fn sync_data(&self) {
+        *self.data.lock().unwrap() = self.into();
+    }
+
+    /// Refreshes the statistics from REST when the websocket stopped sending them
+    #[tracing::instrument(skip(self))]
+    async fn refresh_stale_statistics(&mut self) {
+        self.statistics_stale = true;
+        self.sync_data();
+
+        tracing::warn!(
+            "Lavalink Node {} did not send statistics for {} second(s). Refreshing from REST",
+            self.name,
+            STATS_STALE_AFTER.as_secs()
+        );
+
+        match self.rest.stats().await {
+            Ok(data) => self.update_statistics(data),
+            Err(error) => {
+                // try again on the next interval instead of every loop
+                self.statistics_received_at = Instant::now();
+
+                tracing::warn!(
+                    "Lavalink Node {} failed to refresh statistics from REST => {:?}",
+                    self.name,
+                    error
+                );
+            }
         }
     }
 
@@ -263,11 +1038,30 @@ impl NodeManager {
             return Ok(());
         }
 
+        if self.cancellation_token.is_cancelled() {
+            return Err(LavalinkNodeError::Cancelled);
+        }
+
+        // a connection that stayed up long enough was healthy, the attempts before it no longer count
+        if self
+            .connected_at
+            .take()
+            .is_some_and(|at| at.elapsed() >= self.stable_after)
+        {
+            self.reconnects = 0;
+        }
+
+        // the connection was lost or closed on request if this node was connected before
+        let reconnecting = matches!(
+            self.state,
+            NodeState::Connected | NodeState::Reconnecting { .. }
+        );
+
         loop {
             let key = generate_key();
             let mut request = Request::builder()
                 .method("GET")
-                .header("Host", &self.url)
+                .header("Host", &self.host)
                 .header("Connection", "Upgrade")
                 .header("Upgrade", "websocket")
                 .header("Sec-WebSocket-Version", "13")
@@ -288,7 +1082,7 @@ impl NodeManager {
             };
 
             pairs.insert("Session-Id", &session_id);
-            pairs.insert("Client-Name", &self.user_agent);
+            pairs.insert("Client-Name", &self.client_name);
             pairs.insert("User-Agent", &self.user_agent);
 
             let headers = request.headers_mut();
@@ -297,8 +1091,18 @@ impl NodeManager {
                 headers.append(*key, value.parse()?);
             }
 
+            headers.extend(self.headers.clone());
+
             self.reconnects += 1;
 
+            self.set_state(if reconnecting {
+                NodeState::Reconnecting {
+                    attempt: self.reconnects,
+                }
+            } else {
+                NodeState::Connecting
+            });
+
             tracing::debug!(
                 "Lavalink Node {} Connecting to {} [Retries: {}]",
                 self.name,
@@ -310,6 +1114,33 @@ impl NodeManager {
                 break;
             };
 
+            // the node answers the same way until the credentials change, so retrying is pointless
+            if let LavalinkNodeError::InvalidCredentials(status) = result {
+                tracing::error!(
+                    "Lavalink Node {} rejected the credentials with HTTP {}, not retrying",
+                    self.name,
+                    status
+                );
+
+                self.state_error = Some(result.to_string());
+                self.reconnects = 0;
+                self.set_state(NodeState::Disconnected);
+
+                return Err(result);
+            }
+
+            self.state_error = Some(result.to_string());
+            self.failures = self.failures.saturating_add(1);
+
+            let max_failures = self.quarantine.as_ref().map(|options| options.max_failures);
+
+            if Some(self.failures) == max_failures {
+                self.quarantine_node(format!(
+                    "{} connection attempts in a row failed",
+                    self.failures
+                ));
+            }
+
             if self.reconnects < self.reconnect_tries {
                 let duration = Duration::from_secs(5);
 
@@ -320,29 +1151,169 @@ impl NodeManager {
                     duration.as_secs()
                 );
 
-                sleep(duration).await;
-
-                continue;
+                tokio::select! {
+                    _ = sleep(duration) => continue,
+                    _ = self.cancellation_token.cancelled() => {
+                        self.reconnects = 0;
+                        self.set_state(NodeState::Disconnected);
+                        return Err(LavalinkNodeError::Cancelled);
+                    }
+                }
             }
 
             self.reconnects = 0;
+            self.set_state(NodeState::Disconnected);
 
             return Err(result);
         }
 
-        self.reconnects = 0;
+        // an outdated node would fail on the endpoints it misses later on, so refuse it right away
+        if let Err(error) = self.check_version().await {
+            tracing::error!("Lavalink Node {} was refused => {}", self.name, error);
+
+            self.connection.disconnect().await;
+            self.state_error = Some(error.to_string());
+            self.reconnects = 0;
+            self.set_state(NodeState::Disconnected);
+
+            return Err(error);
+        }
+
+        self.connected_at = Some(Instant::now());
+        self.failures = 0;
+        self.set_state(NodeState::Connected);
 
         Ok(())
     }
 
+    /// Checks the node runs a version its options allow
+    /// # Nodes are not refused when their info can't be fetched, only when their version is known to not be allowed
+    async fn check_version(&self) -> Result<(), LavalinkNodeError> {
+        let Some(required) = &self.min_version else {
+            return Ok(());
+        };
+
+        let info = match self.rest.info().await {
+            Ok(info) => info,
+            Err(error) => {
+                tracing::warn!(
+                    "Lavalink Node {} failed to fetch its info to check its version => {:?}",
+                    self.name,
+                    error
+                );
+
+                return Ok(());
+            }
+        };
+
+        let version = Version::parse(&info.version.semver).unwrap_or_else(|_| {
+            Version::new(info.version.major, info.version.minor, info.version.patch)
+        });
+
+        if required.matches(&version) {
+            return Ok(());
+        }
+
+        // no subscribers is not an error
+        self.node_event_sender
+            .send(NodeEvent::UnsupportedVersion {
+                name: self.name.clone(),
+                version: version.to_string(),
+                required: required.to_string(),
+            })
+            .ok();
+
+        Err(LavalinkNodeError::UnsupportedVersion {
+            version: version.to_string(),
+            required: required.to_string(),
+        })
+    }
+
+    /// Updates the authentication key of this node, then reconnects the websocket with it
+    #[tracing::instrument(skip(self, auth))]
+    pub async fn update_auth(&mut self, auth: String) -> Result<(), LavalinkNodeError> {
+        // the session still accepts the key it was opened with
+        self.hold_players().await;
+
+        self.rest_auth.write().await.clone_from(&auth);
+
+        self.auth = auth;
+        self.sync_data();
+
+        tracing::info!("Lavalink Node {} authentication key updated", self.name);
+
+        self.reopen().await
+    }
+
+    /// Connects again with a new session, after lavalink reported the current one as missing
+    #[tracing::instrument(skip(self))]
+    async fn refresh_session(&mut self) -> Result<(), LavalinkNodeError> {
+        // several requests can fail at once, only refresh once for them
+        self.receivers.session_invalidated.drain();
+
+        tracing::warn!(
+            "Lavalink Node {} session no longer exists. Connecting with a new session...",
+            self.name
+        );
+
+        self.session_id.write().await.take();
+
+        // the players lived on the missing session, so they are gone on lavalink too
+        self.send_players_destroy(DestroyReason::NodeFailed).await;
+
+        self.reconnect().await
+    }
+
+    /// Closes the websocket then connects again, keeping the players of this node
+    /// # Players are kept by resuming, which is enabled for the reconnect if this node has it disabled. They receive a destroy event if lavalink can't resume
+    #[tracing::instrument(skip(self))]
+    pub async fn reconnect(&mut self) -> Result<(), LavalinkNodeError> {
+        self.hold_players().await;
+        self.reopen().await
+    }
+
+    /// Closes the websocket then connects again
+    async fn reopen(&mut self) -> Result<(), LavalinkNodeError> {
+        self.connection.disconnect().await;
+
+        tracing::info!("Lavalink Node {} Reconnecting...", self.name);
+
+        self.connect().await
+    }
+
+    /// Enables resuming on the current session if this node has it disabled, so lavalink keeps the players until it reconnects
+    async fn hold_players(&mut self) {
+        if self.resume_timeout.is_some()
+            || !self.connection.available()
+            || self.session_id.read().await.is_none()
+        {
+            return;
+        }
+
+        if let Err(error) = self
+            .rest
+            .update_session(SessionInfo::new(true, RECONNECT_RESUME_TIMEOUT))
+            .await
+        {
+            tracing::warn!(
+                "Lavalink Node {} failed to enable resuming for the reconnect, its players will be destroyed => {:?}",
+                self.name,
+                error
+            );
+        }
+    }
+
     /// Disconnects this node
     #[tracing::instrument(skip(self))]
     pub async fn disconnect(&mut self) {
         self.connection.disconnect().await;
 
-        self.send_players_destroy().await;
+        self.send_players_destroy(DestroyReason::NodeDisconnected)
+            .await;
 
         self.reconnects = 0;
+        self.connected_at = None;
+        self.set_state(NodeState::Disconnected);
 
         tracing::info!("Lavalink Node {} Disconnected...", self.name);
     }
@@ -353,16 +1324,49 @@ impl NodeManager {
         self.disconnect().await;
 
         self.destroyed = true;
+        self.set_state(NodeState::Destroyed);
+    }
+
+    /// Closes the websocket and stops this node without destroying its players,
+    /// so lavalink keeps them on the session until it is resumed
+    #[tracing::instrument(skip(self))]
+    pub async fn shutdown(&mut self) {
+        self.connection.disconnect().await;
+
+        self.connected_at = None;
+        self.destroyed = true;
+        self.shut_down = true;
+        self.set_state(NodeState::Destroyed);
+
+        tracing::info!("Lavalink Node {} Shut down...", self.name);
     }
 }
 
 /// Interface to communicate with the websocket
 #[derive(Clone, Debug)]
 pub struct Node {
+    /// Name of this node
+    pub name: String,
     /// Rest interface for this node
     pub rest: Rest,
     /// List of subscribers for this node player events, mapped by Guild Id and It's sender
-    pub events_sender: Arc<ConcurrentHashMap<u64, FlumeSender<EventType>>>,
+    pub events_sender: Arc<ConcurrentHashMap<GuildId, EventSender>>,
+    /// Last voice data sent for each player, mapped by Guild Id
+    pub voices: Arc<ConcurrentHashMap<GuildId, LavalinkVoice>>,
+    /// Track each player is playing, mapped by Guild Id
+    pub now_playing: Arc<ConcurrentHashMap<GuildId, Track>>,
+    /// Queues of every player
+    pub queues: Queues,
+    /// Presets players can apply by name
+    pub presets: Presets,
+    /// Node every player is on, shared by all nodes so players can move between them
+    pub(crate) locations: Arc<ConcurrentHashMap<GuildId, PlayerLocation>>,
+    stats_sender: BroadcastSender<Stats>,
+    state_sender: BroadcastSender<NodeStateChange>,
+    node_event_sender: BroadcastSender<NodeEvent>,
+    stats_history: Arc<Mutex<VecDeque<Stats>>>,
+    data: Arc<Mutex<NodeManagerData>>,
+    throughput: Arc<Mutex<Throughput>>,
     commands_sender: FlumeSender<WebsocketCommand>,
 }
 
@@ -370,24 +1374,30 @@ impl Node {
     /// Creates a new Node interface and underlying worker
     pub async fn new(
         options: NodeManagerOptions<'_>,
-    ) -> Result<(Self, JoinHandle<String>), LavalinkNodeError> {
+    ) -> Result<(Self, JoinHandle<(String, bool)>), LavalinkNodeError> {
         let (commands_sender, commands_receiver) = unbounded::<WebsocketCommand>();
 
-        let mut manager = NodeManager::new(&options, commands_receiver);
+        let mut manager = NodeManager::new(&options, commands_receiver)?;
 
         manager.connect().await?;
 
-        let rest = Rest::new(RestOptions {
-            request: options.request,
-            url: format!("http://{}:{}/v4", options.host, options.port),
-            auth: options.auth,
-            user_agent: options.user_agent,
-            session_id: manager.session_id.clone(),
-        });
+        let rest = manager.rest.clone();
 
         let node = Self {
+            name: options.name.to_string(),
             rest,
             events_sender: manager.event_senders.clone(),
+            voices: manager.voices.clone(),
+            now_playing: manager.now_playing.clone(),
+            queues: manager.queues.clone(),
+            presets: options.presets.clone(),
+            locations: options.locations.clone(),
+            stats_sender: manager.stats_sender.clone(),
+            state_sender: manager.state_sender.clone(),
+            node_event_sender: manager.node_event_sender.clone(),
+            stats_history: manager.stats_history.clone(),
+            data: manager.data.clone(),
+            throughput: manager.connection.throughput(),
             commands_sender,
         };
 
@@ -397,7 +1407,9 @@ impl Node {
                 manager.name
             );
 
-            if let Err(error) = manager.start().await {
+            let result = manager.start().await;
+
+            if let Err(error) = &result {
                 tracing::error!(
                     "Lavalink Node {} threw an unrecoverable error. Cleaning up! => {:?}",
                     manager.name,
@@ -405,7 +1417,7 @@ impl Node {
                 );
             }
 
-            manager.name
+            (manager.name, result.is_err())
         });
 
         Ok((node, handle))
@@ -422,6 +1434,66 @@ impl Node {
         receiver.await?
     }
 
+    /// Gets the last known node data without waiting on the worker of this node
+    /// # Updated every time the worker receives statistics, so it can be slightly behind [`Node::data`]
+    pub fn cached_data(&self) -> NodeManagerData {
+        self.data.lock().unwrap().clone()
+    }
+
+    /// Gets the connection state of this node
+    pub fn state(&self) -> NodeState {
+        self.data.lock().unwrap().state
+    }
+
+    /// If the websocket of this node is connected
+    pub fn is_connected(&self) -> bool {
+        self.state() == NodeState::Connected
+    }
+
+    /// Subscribes to the statistics updates this node receives from lavalink
+    /// # Slow subscribers skip the oldest updates instead of blocking the node
+    pub fn stats_updates(&self) -> BroadcastReceiver<Stats> {
+        self.stats_sender.subscribe()
+    }
+
+    /// Last statistics this node received, oldest first
+    pub fn stats_history(&self) -> Vec<Stats> {
+        self.stats_history.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Traffic the websocket of this node received, to spot connections that silently stalled
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.throughput.lock().unwrap().stats()
+    }
+
+    /// Subscribes to the connection state transitions of this node
+    /// # Slow subscribers skip the oldest transitions instead of blocking the node
+    pub fn state_changes(&self) -> BroadcastReceiver<NodeStateChange> {
+        self.state_sender.subscribe()
+    }
+
+    /// Gets the info of this node, fetched once and cached until refreshed or the node restarts
+    pub async fn info_cached(&self) -> Result<LavalinkInfo, LavalinkRestError> {
+        self.rest.info_cached().await
+    }
+
+    /// Gets what this node supports, from its cached info
+    pub async fn capabilities(&self) -> Result<NodeCapabilities, LavalinkRestError> {
+        Ok((&self.info_cached().await?).into())
+    }
+
+    /// Fetches the info of this node again, replacing the cached info and version
+    pub async fn refresh_info(&self) -> Result<LavalinkInfo, LavalinkRestError> {
+        self.rest.clear_info_cache().await;
+        self.rest.info_cached().await
+    }
+
+    /// Subscribes to the health alerts of this node, like degradation
+    /// # Slow subscribers skip the oldest alerts instead of blocking the node
+    pub fn node_events(&self) -> BroadcastReceiver<NodeEvent> {
+        self.node_event_sender.subscribe()
+    }
+
     /// Connects this node
     pub async fn connect(&self) -> Result<(), LavalinkNodeError> {
         let (sender, receiver) = channel::<Result<(), LavalinkNodeError>>();
@@ -433,6 +1505,80 @@ impl Node {
         receiver.await?
     }
 
+    /// Updates the authentication key used by this node, then reconnects with it
+    /// # Players are kept the way [`Node::reconnect`] keeps them, so lavalink has to accept the new key on the same session
+    pub async fn update_auth(&self, auth: &str) -> Result<(), LavalinkNodeError> {
+        let (sender, receiver) = channel::<Result<(), LavalinkNodeError>>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::UpdateAuth(auth.to_string(), sender))
+            .await?;
+
+        receiver.await?
+    }
+
+    /// Reconnects the websocket of this node without destroying its players
+    /// # Resuming is enabled for the reconnect if this node has it disabled. Players receive a destroy event if lavalink can't resume (e.g. it restarted)
+    pub async fn reconnect(&self) -> Result<(), LavalinkNodeError> {
+        let (sender, receiver) = channel::<Result<(), LavalinkNodeError>>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::Reconnect(sender))
+            .await?;
+
+        receiver.await?
+    }
+
+    /// Sends a raw message to the node through its websocket, for plugins that listen to the client
+    pub async fn send_raw(&self, message: Value) -> Result<(), LavalinkNodeError> {
+        let (sender, receiver) = channel::<Result<(), LavalinkNodeError>>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::SendRaw(message, sender))
+            .await?;
+
+        receiver.await?
+    }
+
+    /// Enables or disables resuming for the session of this node, timeout is in seconds
+    ///
+    /// The node remembers it, so players are kept through disconnects and resuming is enabled again after reconnects
+    pub async fn set_resuming(
+        &self,
+        enabled: bool,
+        timeout: u32,
+    ) -> Result<SessionInfo, LavalinkRestError> {
+        let session = self
+            .rest
+            .update_session(SessionInfo::new(enabled, timeout))
+            .await?;
+
+        let (sender, receiver) = channel::<()>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::SetResumeTimeout(
+                session.resuming.then_some(session.timeout),
+                sender,
+            ))
+            .await
+            .map_err(LavalinkNodeError::from)?;
+
+        receiver.await.map_err(LavalinkNodeError::from)?;
+
+        Ok(session)
+    }
+
+    /// Marks this node as draining, so it's never picked for new players while its current players stay on it
+    pub async fn set_draining(&self, draining: bool) -> Result<(), LavalinkNodeError> {
+        let (sender, receiver) = channel::<()>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::SetDraining(draining, sender))
+            .await?;
+
+        Ok(receiver.await?)
+    }
+
     /// Disconnects this node
     pub async fn disconnect(&self) -> Result<(), LavalinkNodeError> {
         let (sender, receiver) = channel::<()>();
@@ -454,4 +1600,15 @@ impl Node {
 
         Ok(receiver.await?)
     }
+
+    /// Closes the websocket and stops this node, without sending destroy events to its players
+    pub async fn shutdown(&self) -> Result<(), LavalinkNodeError> {
+        let (sender, receiver) = channel::<()>();
+
+        self.commands_sender
+            .send_async(WebsocketCommand::Shutdown(sender))
+            .await?;
+
+        Ok(receiver.await?)
+    }
 }
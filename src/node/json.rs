@@ -0,0 +1,25 @@
+use serde::de::DeserializeOwned;
+use std::result::Result;
+use tokio_tungstenite::tungstenite::Bytes;
+
+/// Error returned by the json parser in use
+#[cfg(not(feature = "simd-json"))]
+pub type JsonError = serde_json::Error;
+
+/// Error returned by the json parser in use
+#[cfg(feature = "simd-json")]
+pub type JsonError = simd_json::Error;
+
+/// Parses a json payload with serde_json
+#[cfg(not(feature = "simd-json"))]
+pub fn from_bytes<T: DeserializeOwned>(bytes: Bytes) -> Result<T, JsonError> {
+    serde_json::from_slice(&bytes)
+}
+
+/// Parses a json payload with simd-json, which needs the payload to be mutable
+#[cfg(feature = "simd-json")]
+pub fn from_bytes<T: DeserializeOwned>(bytes: Bytes) -> Result<T, JsonError> {
+    let mut bytes = Vec::from(bytes);
+
+    simd_json::serde::from_slice(&mut bytes)
+}
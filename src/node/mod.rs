@@ -1,6 +1,19 @@
+/// Cache of resolve results shared by every node
+pub mod cache;
 /// Websocket client
 pub mod client;
+/// JSON parsing, backed by simd-json when its feature is enabled
+pub(crate) mod json;
+/// Recorder of the raw traffic of a node, and its replayer
+#[cfg(feature = "debug-recorder")]
+pub mod recorder;
 /// Rest for Websocket client
 pub mod rest;
+/// Scheduler that queues the REST requests of every node by priority
+pub mod scheduler;
+/// TLS configuration for REST and websocket connections
+pub mod tls;
+/// Pluggable transports REST requests and websockets go through
+pub mod transport;
 /// Wrapper around websocket data receiving
 pub mod websocket;
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Error as IoError, ErrorKind, Write};
+use std::path::Path;
+use std::result::Result;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_tungstenite::tungstenite::Bytes;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::http::{Method, StatusCode};
+
+use crate::model::node::{LavalinkInfo, LavalinkMessage, RoutePlanner, SessionInfo, Stats};
+use crate::model::player::{DataType, LavalinkPlayer, Track};
+use crate::node::json::{self, JsonError};
+use crate::node::transport::HttpRequest;
+
+/// Line of a recording
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedEntry {
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    /// Name of the node the traffic is from
+    pub node: String,
+    #[serde(flatten)]
+    pub traffic: RecordedTraffic,
+}
+
+/// Traffic a recording line holds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedTraffic {
+    /// Raw text frame the node sent on its websocket
+    WsFrame { body: String },
+    /// REST request sent to the node, its Authorization header redacted
+    RestRequest {
+        method: String,
+        url: String,
+        headers: HashMap<String, String>,
+        body: Option<String>,
+    },
+    /// Raw body the node responded with
+    RestResponse {
+        method: String,
+        url: String,
+        status: u16,
+        body: String,
+    },
+}
+
+/// Writes the raw traffic of a node to a NDJSON file, to diagnose parse failures against third-party servers
+#[derive(Debug)]
+pub struct TrafficRecorder {
+    node: String,
+    file: Mutex<File>,
+}
+
+impl TrafficRecorder {
+    /// Opens the file to record to, appending to it if it exists
+    pub fn create(node: impl Into<String>, path: impl AsRef<Path>) -> Result<Self, IoError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            node: node.into(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Records a raw websocket frame
+    pub fn record_frame(&self, body: &str) {
+        self.write(RecordedTraffic::WsFrame {
+            body: body.to_string(),
+        });
+    }
+
+    /// Records a REST request, redacting its credentials
+    pub fn record_request(&self, request: &HttpRequest) {
+        let headers = request
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let value = match *name == AUTHORIZATION {
+                    true => "[redacted]".to_string(),
+                    false => String::from_utf8_lossy(value.as_bytes()).to_string(),
+                };
+
+                (name.to_string(), value)
+            })
+            .collect();
+
+        self.write(RecordedTraffic::RestRequest {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            headers,
+            body: request.body.clone(),
+        });
+    }
+
+    /// Records the raw body of a REST response
+    pub fn record_response(&self, method: &Method, url: &str, status: StatusCode, body: &Bytes) {
+        self.write(RecordedTraffic::RestResponse {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: status.as_u16(),
+            body: String::from_utf8_lossy(body).to_string(),
+        });
+    }
+
+    fn write(&self, traffic: RecordedTraffic) {
+        let entry = RecordedEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_millis() as u64)
+                .unwrap_or_default(),
+            node: self.node.clone(),
+            traffic,
+        };
+
+        let Ok(line) = serde_json::to_string(&entry) else {
+            return;
+        };
+
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+
+        // recording is best effort, it should never break the node
+        if let Err(error) = writeln!(file, "{line}") {
+            tracing::warn!(
+                "Failed to record traffic of node {} => {}",
+                self.node,
+                error
+            );
+        }
+    }
+}
+
+/// Entry of a recording fed back through the deserialization
+#[derive(Debug)]
+pub struct Replayed {
+    pub entry: RecordedEntry,
+    /// Error the entry failed to parse with, none if it parsed or there was nothing to parse
+    pub error: Option<JsonError>,
+}
+
+/// Feeds every websocket frame and REST response of a recording back through the deserialization Anchorage uses
+pub fn replay(path: impl AsRef<Path>) -> Result<Vec<Replayed>, IoError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut replayed = vec![];
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = serde_json::from_str::<RecordedEntry>(&line)
+            .map_err(|error| IoError::new(ErrorKind::InvalidData, error))?;
+
+        let error = match &entry.traffic {
+            RecordedTraffic::WsFrame { body } => {
+                json::from_bytes::<LavalinkMessage>(Bytes::from(body.clone())).err()
+            }
+            RecordedTraffic::RestResponse {
+                method,
+                url,
+                status,
+                body,
+            } if (200..300).contains(status) && !body.is_empty() => {
+                parse_response(method, url, body).err()
+            }
+            _ => None,
+        };
+
+        replayed.push(Replayed { entry, error });
+    }
+
+    Ok(replayed)
+}
+
+/// Parses a REST response into the model its endpoint returns
+fn parse_response(method: &str, url: &str, body: &str) -> Result<(), JsonError> {
+    let path = url.split('?').next().unwrap_or(url);
+    let bytes = Bytes::from(body.to_string());
+
+    if path.ends_with("/loadtracks") {
+        json::from_bytes::<DataType>(bytes)?;
+    } else if path.ends_with("/decodetrack") {
+        json::from_bytes::<Track>(bytes)?;
+    } else if path.ends_with("/players") {
+        json::from_bytes::<Vec<LavalinkPlayer>>(bytes)?;
+    } else if path.contains("/players/") {
+        json::from_bytes::<LavalinkPlayer>(bytes)?;
+    } else if path.contains("/sessions/") && method == "PATCH" {
+        json::from_bytes::<SessionInfo>(bytes)?;
+    } else if path.ends_with("/stats") {
+        json::from_bytes::<Stats>(bytes)?;
+    } else if path.ends_with("/info") {
+        json::from_bytes::<LavalinkInfo>(bytes)?;
+    } else if path.ends_with("/routeplanner/status") {
+        json::from_bytes::<RoutePlanner>(bytes)?;
+    }
+
+    Ok(())
+}
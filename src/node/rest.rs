@@ -1,37 +1,84 @@
-use reqwest::{Client, RequestBuilder};
-use serde::Deserialize;
+use flume::Sender as FlumeSender;
+use futures::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use serde_json::to_string;
+use std::fmt::{Debug, Formatter};
 use std::result::Result;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Bytes;
+use tokio_tungstenite::tungstenite::http::header::{AUTHORIZATION, USER_AGENT};
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use url::Url;
 
+use crate::audit::AuditSink;
 use crate::model::anchorage::RestOptions;
 use crate::model::error::LavalinkRestError;
+use crate::model::id::GuildId;
 use crate::model::node::{LavalinkInfo, RoutePlanner, SessionInfo, Stats};
 use crate::model::player::{DataType, LavalinkPlayer, LavalinkPlayerOptions, Track};
+use crate::node::cache::ResolveCache;
+use crate::node::json;
+#[cfg(feature = "debug-recorder")]
+use crate::node::recorder::TrafficRecorder;
+use crate::node::scheduler::{RequestPriority, RestScheduler};
+use crate::node::transport::{HttpRequest, HttpTransport};
 
-#[derive(Clone, Debug)]
+/// Maximum amount of load track requests [`Rest::resolve_many`] sends at the same time
+pub const RESOLVE_MANY_CONCURRENCY: usize = 8;
+
+#[derive(Clone)]
 pub struct Rest {
-    /// Request client this rest will use
-    pub request: Client,
+    /// Transport this rest sends its requests through
+    pub transport: Arc<dyn HttpTransport>,
     /// Base url to use
     pub url: String,
-    /// Authorization key to use
-    pub auth: String,
     /// User-Agent to use on requests
     pub user_agent: String,
+    /// Extra headers to send on requests
+    pub headers: HeaderMap,
+    auth: Arc<RwLock<String>>,
     session_id: Arc<RwLock<Option<String>>>,
+    session_invalidated: Option<FlumeSender<()>>,
+    resolve_cache: Option<Arc<ResolveCache>>,
+    scheduler: Option<Arc<RestScheduler>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "debug-recorder")]
+    recorder: Option<Arc<TrafficRecorder>>,
+    /// Info and version of the server, which don't change until it restarts
+    info_cache: Arc<RwLock<Option<LavalinkInfo>>>,
+    version_cache: Arc<RwLock<Option<String>>>,
+}
+
+impl Debug for Rest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rest")
+            .field("url", &self.url)
+            .field("user_agent", &self.user_agent)
+            .finish()
+    }
 }
 
 impl Rest {
     /// Creates a new Rest that is tied to a node
     pub fn new(options: RestOptions) -> Self {
         Self {
-            request: options.request,
+            transport: options.transport,
             url: options.url,
-            auth: options.auth.to_string(),
+            auth: options.auth,
             user_agent: options.user_agent.to_string(),
+            headers: options.headers,
             session_id: options.session_id,
+            session_invalidated: options.session_invalidated,
+            resolve_cache: options.resolve_cache,
+            scheduler: options.scheduler,
+            audit_sink: options.audit_sink,
+            #[cfg(feature = "debug-recorder")]
+            recorder: options.recorder,
+            info_cache: Arc::new(RwLock::new(None)),
+            version_cache: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -41,53 +88,127 @@ impl Rest {
         option.ok_or(LavalinkRestError::NoSessionId)
     }
 
+    /// Cache of resolve results this rest uses, if enabled
+    pub fn resolve_cache(&self) -> Option<&ResolveCache> {
+        self.resolve_cache.as_deref()
+    }
+
     /// Tries to resolve a link, or a search term with prefix
+    ///
+    /// Tracks, playlists, and search results are served from the resolve cache when enabled
     pub async fn resolve(&self, identifier: &str) -> Result<DataType, LavalinkRestError> {
-        let request = self
-            .request
-            .get(format!("{}/loadtracks", self.url))
-            .query(&[("identifier", identifier)]);
+        if let Some(data) = self
+            .resolve_cache
+            .as_ref()
+            .and_then(|cache| cache.get(identifier))
+        {
+            return Ok(data);
+        }
+
+        let request = HttpRequest::new(
+            Method::GET,
+            Self::with_query(
+                &format!("{}/loadtracks", self.url),
+                &[("identifier", identifier)],
+            )?,
+        );
 
-        self.make_request::<DataType>(request)
+        let data = self
+            .make_request::<DataType>(request, RequestPriority::Normal)
             .await?
-            .ok_or(LavalinkRestError::NothingReturned)
+            .ok_or(LavalinkRestError::NothingReturned)?;
+
+        if let Some(cache) = &self.resolve_cache
+            && matches!(
+                data,
+                DataType::Track(_) | DataType::Playlist(_) | DataType::Search(_)
+            )
+        {
+            cache.insert(identifier, data.clone());
+        }
+
+        Ok(data)
+    }
+
+    /// Resolves many identifiers at once, returning the results in the same order
+    ///
+    /// At most [`RESOLVE_MANY_CONCURRENCY`] requests are in flight at the same time
+    pub async fn resolve_many(
+        &self,
+        identifiers: Vec<String>,
+    ) -> Vec<Result<DataType, LavalinkRestError>> {
+        stream::iter(identifiers)
+            .map(|identifier| async move { self.resolve(&identifier).await })
+            .buffered(RESOLVE_MANY_CONCURRENCY)
+            .collect()
+            .await
     }
 
     /// Decodes a base64 lavalink track
     pub async fn decode(&self, track: &str) -> Result<Track, LavalinkRestError> {
-        let request = self
-            .request
-            .get(format!("{}/decodetrack", self.url))
-            .query(&[("track", track)]);
+        let request = HttpRequest::new(
+            Method::GET,
+            Self::with_query(&format!("{}/decodetrack", self.url), &[("track", track)])?,
+        );
 
-        self.make_request::<Track>(request)
+        self.make_request::<Track>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
     /// Gets the player info for a guild
-    pub async fn get_player(&self, guild_id: u64) -> Result<LavalinkPlayer, LavalinkRestError> {
-        let request = self.request.get(format!(
-            "{}/sessions/{}/players/{}",
-            self.url,
-            self.get_session_id().await?,
-            guild_id
-        ));
+    pub async fn get_player(
+        &self,
+        guild_id: impl Into<GuildId>,
+    ) -> Result<LavalinkPlayer, LavalinkRestError> {
+        let request = HttpRequest::new(
+            Method::GET,
+            format!(
+                "{}/sessions/{}/players/{}",
+                self.url,
+                self.get_session_id().await?,
+                guild_id.into()
+            ),
+        );
 
-        self.make_request::<LavalinkPlayer>(request)
+        self.make_request::<LavalinkPlayer>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
+    /// Voice updates keep players connected so they go first, filter tweaks can wait
+    fn update_priority(options: &LavalinkPlayerOptions) -> RequestPriority {
+        if options.voice.is_some() {
+            return RequestPriority::High;
+        }
+
+        let filters_only = options.filters.is_some()
+            && options.track.is_none()
+            && options.identifier.is_none()
+            && options.position.is_none()
+            && options.end_time.is_none()
+            && options.volume.is_none()
+            && options.paused.is_none();
+
+        if filters_only {
+            RequestPriority::Low
+        } else {
+            RequestPriority::Normal
+        }
+    }
+
     /// Gets all the players in this node where this rest is attached to
     pub async fn get_players(&self) -> Result<Vec<LavalinkPlayer>, LavalinkRestError> {
-        let request = self.request.get(format!(
-            "{}/sessions/{}/players",
-            self.url,
-            self.get_session_id().await?
-        ));
+        let request = HttpRequest::new(
+            Method::GET,
+            format!(
+                "{}/sessions/{}/players",
+                self.url,
+                self.get_session_id().await?
+            ),
+        );
 
-        self.make_request::<Vec<LavalinkPlayer>>(request)
+        self.make_request::<Vec<LavalinkPlayer>>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
@@ -95,124 +216,244 @@ impl Rest {
     /// Updates a player
     pub async fn update_player(
         &self,
-        guild_id: u64,
+        guild_id: impl Into<GuildId>,
         no_replace: bool,
         options: LavalinkPlayerOptions,
     ) -> Result<LavalinkPlayer, LavalinkRestError> {
-        let request = self
-            .request
-            .patch(format!(
-                "{}/sessions/{}/players/{}",
-                self.url,
-                self.get_session_id().await?,
-                guild_id
-            ))
-            .query(&[("noReplace", &no_replace)])
-            .header("Content-Type", "application/json")
-            .body(to_string(&options)?);
+        let priority = Self::update_priority(&options);
+
+        let url = format!(
+            "{}/sessions/{}/players/{}",
+            self.url,
+            self.get_session_id().await?,
+            guild_id.into()
+        );
+
+        let request = HttpRequest::new(
+            Method::PATCH,
+            Self::with_query(&url, &[("noReplace", &no_replace.to_string())])?,
+        )
+        .json(to_string(&options)?);
 
-        self.make_request::<LavalinkPlayer>(request)
+        self.make_request::<LavalinkPlayer>(request, priority)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
     /// Destroys a player
-    pub async fn destroy_player(&self, guild_id: u64) -> Result<(), LavalinkRestError> {
-        let request = self.request.delete(format!(
-            "{}/sessions/{}/players/{}",
-            self.url,
-            self.get_session_id().await?,
-            guild_id
-        ));
+    pub async fn destroy_player(
+        &self,
+        guild_id: impl Into<GuildId>,
+    ) -> Result<(), LavalinkRestError> {
+        let request = HttpRequest::new(
+            Method::DELETE,
+            format!(
+                "{}/sessions/{}/players/{}",
+                self.url,
+                self.get_session_id().await?,
+                guild_id.into()
+            ),
+        );
 
-        self.make_request::<()>(request).await?;
+        self.make_request::<()>(request, RequestPriority::Normal)
+            .await?;
 
         Ok(())
     }
 
-    /// Updates the current session (for resuming capabilities)
+    /// Updates the current session (for resuming capabilities), returning the session lavalink confirmed
     pub async fn update_session(
         &self,
         options: SessionInfo,
     ) -> Result<SessionInfo, LavalinkRestError> {
-        let request = self
-            .request
-            .patch(format!(
-                "{}/sessions/{}",
-                self.url,
-                self.get_session_id().await?
-            ))
-            .body(to_string(&options)?);
+        let request = HttpRequest::new(
+            Method::PATCH,
+            format!("{}/sessions/{}", self.url, self.get_session_id().await?),
+        )
+        .json(to_string(&options)?);
 
-        self.make_request::<SessionInfo>(request)
+        self.make_request::<SessionInfo>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
     /// Gets the current statistics of the lavalink server
     pub async fn stats(&self) -> Result<Stats, LavalinkRestError> {
-        let request = self.request.get(format!("{}/stats", self.url));
+        let request = HttpRequest::new(Method::GET, format!("{}/stats", self.url));
 
-        self.make_request::<Stats>(request)
+        self.make_request::<Stats>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
     /// Gets the route planner status of this lavalink server
     pub async fn route_planner_status(&self) -> Result<RoutePlanner, LavalinkRestError> {
-        let request = self
-            .request
-            .get(format!("{}/routeplanner/status", self.url));
+        let request = HttpRequest::new(Method::GET, format!("{}/routeplanner/status", self.url));
 
-        self.make_request::<RoutePlanner>(request)
+        self.make_request::<RoutePlanner>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
     /// Unmarks a failed ip address on your ip rotator
     pub async fn unmark_failed_address(&self, address: &str) -> Result<(), LavalinkRestError> {
-        let request = self
-            .request
-            .post(format!("{}/routeplanner/free/address", self.url))
-            .header("Content-Type", "application/json")
-            .body(format!("{{ address:{address} }}"));
+        let request = HttpRequest::new(
+            Method::POST,
+            format!("{}/routeplanner/free/address", self.url),
+        )
+        .json(format!("{{ address:{address} }}"));
 
-        self.make_request::<()>(request).await?;
+        self.make_request::<()>(request, RequestPriority::Normal)
+            .await?;
 
         Ok(())
     }
 
     /// Grabs the info of the lavalink server
     pub async fn info(&self) -> Result<LavalinkInfo, LavalinkRestError> {
-        let request = self.request.get(format!("{}/info", self.url));
+        let request = HttpRequest::new(Method::GET, format!("{}/info", self.url));
 
-        self.make_request::<LavalinkInfo>(request)
+        self.make_request::<LavalinkInfo>(request, RequestPriority::Normal)
             .await?
             .ok_or(LavalinkRestError::NothingReturned)
     }
 
-    /// Creates a request
-    async fn make_request<T: for<'de> Deserialize<'de>>(
+    /// Grabs the info of the lavalink server once, then serves it from the cache until cleared
+    pub async fn info_cached(&self) -> Result<LavalinkInfo, LavalinkRestError> {
+        if let Some(info) = self.info_cache.read().await.as_ref() {
+            return Ok(info.clone());
+        }
+
+        let info = self.info().await?;
+
+        *self.info_cache.write().await = Some(info.clone());
+
+        Ok(info)
+    }
+
+    /// Grabs the version of the lavalink server
+    pub async fn version(&self) -> Result<String, LavalinkRestError> {
+        // the version endpoint is not versioned like the rest of the api
+        let base = self.url.trim_end_matches("/v4");
+        let request = HttpRequest::new(Method::GET, format!("{}/version", base));
+
+        let bytes = self.send_request(request, RequestPriority::Normal).await?;
+
+        Ok(String::from_utf8_lossy(&bytes).trim().to_string())
+    }
+
+    /// Grabs the version of the lavalink server once, then serves it from the cache until cleared
+    pub async fn version_cached(&self) -> Result<String, LavalinkRestError> {
+        if let Some(version) = self.version_cache.read().await.as_ref() {
+            return Ok(version.clone());
+        }
+
+        let version = self.version().await?;
+
+        *self.version_cache.write().await = Some(version.clone());
+
+        Ok(version)
+    }
+
+    /// Forgets the cached info and version, so they are fetched again on the next cached call
+    pub async fn clear_info_cache(&self) {
+        *self.info_cache.write().await = None;
+        *self.version_cache.write().await = None;
+    }
+
+    /// Checks if an error response from lavalink is about a session that doesn't exist
+    fn is_session_not_found(text: &str) -> bool {
+        serde_json::from_str::<Value>(text)
+            .ok()
+            .and_then(|body| body.get("message")?.as_str().map(str::to_lowercase))
+            .is_some_and(|message| message.contains("session not found"))
+    }
+
+    /// Appends a query to an url, encoding its values
+    fn with_query(url: &str, query: &[(&str, &str)]) -> Result<String, LavalinkRestError> {
+        Ok(Url::parse_with_params(url, query)?.to_string())
+    }
+
+    /// Creates a request, parsing the response as json
+    async fn make_request<T: DeserializeOwned>(
         &self,
-        builder: RequestBuilder,
+        request: HttpRequest,
+        priority: RequestPriority,
     ) -> Result<Option<T>, LavalinkRestError> {
-        let request = builder
-            .header("Authorization", self.auth.as_str())
-            .header("User-Agent", self.user_agent.as_str())
-            .build()?;
+        let bytes = self.send_request(request, priority).await?;
+
+        if bytes.is_empty() {
+            return Ok(None);
+        }
 
-        let response = self.request.execute(request).await?;
+        Ok(Some(json::from_bytes::<T>(bytes)?))
+    }
 
-        if !response.status().is_success() {
-            return Err(LavalinkRestError::ResponseReceivedNotOk(response.status()));
+    /// Creates a request, returning the raw response body
+    async fn send_request(
+        &self,
+        mut request: HttpRequest,
+        priority: RequestPriority,
+    ) -> Result<Bytes, LavalinkRestError> {
+        // held until the response is read, so the next request waits for this one
+        let _permit = match &self.scheduler {
+            Some(scheduler) => Some(scheduler.acquire(priority).await),
+            None => None,
+        };
+
+        // the audited copy leaves out the credentials
+        let audited = self.audit_sink.as_ref().map(|sink| {
+            sink.on_rest_request(&request);
+            (request.clone(), Instant::now())
+        });
+
+        let auth = self.auth.read().await.clone();
+
+        request
+            .headers
+            .insert(AUTHORIZATION, HeaderValue::from_str(&auth)?);
+        request
+            .headers
+            .insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)?);
+        request.headers.extend(self.headers.clone());
+
+        #[cfg(feature = "debug-recorder")]
+        let recorded = self.recorder.as_ref().map(|recorder| {
+            recorder.record_request(&request);
+            (request.method.clone(), request.url.clone())
+        });
+
+        let response = self.transport.execute(request).await?;
+
+        #[cfg(feature = "debug-recorder")]
+        if let (Some(recorder), Some((method, url))) = (&self.recorder, &recorded) {
+            recorder.record_response(method, url, response.status, &response.body);
         }
 
-        let text = response.text().await?;
+        if let (Some(sink), Some((request, started))) = (&self.audit_sink, &audited) {
+            sink.on_rest_response(request, &response, started.elapsed());
+        }
 
-        if text.is_empty() {
-            return Ok(None);
+        if response.status == StatusCode::NOT_FOUND {
+            let text = String::from_utf8_lossy(&response.body);
+
+            if !Self::is_session_not_found(&text) {
+                return Err(LavalinkRestError::ResponseReceivedNotOk(
+                    StatusCode::NOT_FOUND,
+                ));
+            }
+
+            if let Some(sender) = &self.session_invalidated {
+                sender.send_async(()).await.ok();
+            }
+
+            return Err(LavalinkRestError::SessionNotFound);
+        }
+
+        if !response.status.is_success() {
+            return Err(LavalinkRestError::ResponseReceivedNotOk(response.status));
         }
 
-        Ok(Some(serde_json::from_str::<T>(&text)?))
+        Ok(response.body)
     }
 }
@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot::{
+    Receiver as TokioOneshotReceiver, Sender as TokioOneshotSender, channel,
+};
+use tokio::time::{Instant, sleep_until};
+
+use crate::model::anchorage::RestSchedulerOptions;
+
+/// Priority of a request waiting on the scheduler, higher ones are sent first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RequestPriority {
+    /// Requests that only change filters
+    Low,
+    /// Every other request
+    Normal,
+    /// Requests that carry voice updates
+    High,
+}
+
+/// A request waiting for its turn
+struct Waiter {
+    priority: RequestPriority,
+    order: u64,
+    sender: TokioOneshotSender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // higher priority first, then the one that waited the longest
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+struct State {
+    running: usize,
+    order: u64,
+    next_start: Instant,
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// Limits and spaces out the requests of every node, so bursts are queued instead of hitting lavalink at once
+pub struct RestScheduler {
+    max_concurrent: usize,
+    min_interval: Duration,
+    state: Mutex<State>,
+}
+
+impl std::fmt::Debug for RestScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+
+        f.debug_struct("RestScheduler")
+            .field("max_concurrent", &self.max_concurrent)
+            .field("min_interval", &self.min_interval)
+            .field("running", &state.running)
+            .field("waiting", &state.waiting.len())
+            .finish()
+    }
+}
+
+/// Turn of a request on the scheduler, the next request is let through once dropped
+pub(crate) struct SchedulerPermit<'a> {
+    scheduler: &'a RestScheduler,
+}
+
+impl Drop for SchedulerPermit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// Hands the turn over to the next request if it was given while the waiting request got cancelled
+struct PendingPermit<'a> {
+    scheduler: &'a RestScheduler,
+    receiver: TokioOneshotReceiver<()>,
+    acquired: bool,
+}
+
+impl Drop for PendingPermit<'_> {
+    fn drop(&mut self) {
+        if !self.acquired && self.receiver.try_recv().is_ok() {
+            self.scheduler.release();
+        }
+    }
+}
+
+impl RestScheduler {
+    /// Creates a scheduler with nothing running
+    pub fn new(options: RestSchedulerOptions) -> Self {
+        Self {
+            max_concurrent: options.max_concurrent.max(1),
+            min_interval: options.min_interval,
+            state: Mutex::new(State {
+                running: 0,
+                order: 0,
+                next_start: Instant::now(),
+                waiting: BinaryHeap::new(),
+            }),
+        }
+    }
+
+    /// Waits for the turn of a request
+    pub(crate) async fn acquire(&self, priority: RequestPriority) -> SchedulerPermit<'_> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+
+            if state.running < self.max_concurrent && state.waiting.is_empty() {
+                state.running += 1;
+                None
+            } else {
+                let (sender, receiver) = channel();
+
+                state.order += 1;
+
+                let order = state.order;
+
+                state.waiting.push(Waiter {
+                    priority,
+                    order,
+                    sender,
+                });
+
+                Some(receiver)
+            }
+        };
+
+        if let Some(receiver) = receiver {
+            let mut pending = PendingPermit {
+                scheduler: self,
+                receiver,
+                acquired: false,
+            };
+
+            // the sender is only dropped with the scheduler, which outlives this borrow
+            (&mut pending.receiver).await.ok();
+            pending.acquired = true;
+        }
+
+        let permit = SchedulerPermit { scheduler: self };
+
+        if !self.min_interval.is_zero() {
+            let start = {
+                let mut state = self.state.lock().unwrap();
+                let start = state.next_start.max(Instant::now());
+
+                state.next_start = start + self.min_interval;
+
+                start
+            };
+
+            sleep_until(start).await;
+        }
+
+        permit
+    }
+
+    /// Gives the turn to the next waiting request, or frees it if none is waiting
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.sender.send(()).is_ok() {
+                return;
+            }
+        }
+
+        state.running = state.running.saturating_sub(1);
+    }
+
+    /// Amount of requests waiting for their turn
+    pub fn waiting(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+}
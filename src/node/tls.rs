@@ -0,0 +1,163 @@
+#[cfg(feature = "reqwest")]
+use reqwest::Client as ReqwestClient;
+use std::result::Result;
+use tokio_tungstenite::Connector;
+
+#[cfg(feature = "reqwest")]
+use crate::model::anchorage::HttpOptions;
+use crate::model::anchorage::TlsOptions;
+use crate::model::error::LavalinkNodeError;
+
+/// Builds a dedicated request client that follows the TLS options of a node
+#[cfg(all(feature = "reqwest", any(feature = "rustls", feature = "native-tls")))]
+pub fn rest_client(
+    options: &TlsOptions,
+    http: &HttpOptions,
+) -> Result<ReqwestClient, LavalinkNodeError> {
+    let mut certificates = vec![];
+
+    for pem in &options.root_certificates {
+        certificates.extend(reqwest::Certificate::from_pem_bundle(pem)?);
+    }
+
+    let client = http
+        .apply(ReqwestClient::builder())
+        .tls_danger_accept_invalid_certs(options.danger_accept_invalid_certs)
+        .tls_certs_merge(certificates)
+        .build()?;
+
+    Ok(client)
+}
+
+/// Builds a dedicated request client that follows the TLS options of a node
+#[cfg(all(
+    feature = "reqwest",
+    not(any(feature = "rustls", feature = "native-tls"))
+))]
+pub fn rest_client(_: &TlsOptions, http: &HttpOptions) -> Result<ReqwestClient, LavalinkNodeError> {
+    Ok(http.apply(ReqwestClient::builder()).build()?)
+}
+
+/// Builds a websocket connector that follows the TLS options of a node
+#[cfg(feature = "rustls")]
+pub fn websocket_connector(options: &TlsOptions) -> Result<Connector, LavalinkNodeError> {
+    use rustls::pki_types::CertificateDer;
+    use rustls::pki_types::pem::PemObject;
+    use rustls::{ClientConfig, RootCertStore};
+    use std::sync::Arc;
+
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|error| LavalinkNodeError::Tls(error.to_string()))?;
+
+    let config = if options.danger_accept_invalid_certs {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(danger::NoCertificateVerification(provider)))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        for pem in &options.root_certificates {
+            for certificate in CertificateDer::pem_slice_iter(pem) {
+                let certificate =
+                    certificate.map_err(|error| LavalinkNodeError::Tls(error.to_string()))?;
+
+                roots
+                    .add(certificate)
+                    .map_err(|error| LavalinkNodeError::Tls(error.to_string()))?;
+            }
+        }
+
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+
+    Ok(Connector::Rustls(Arc::new(config)))
+}
+
+/// Builds a websocket connector that follows the TLS options of a node
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+pub fn websocket_connector(options: &TlsOptions) -> Result<Connector, LavalinkNodeError> {
+    let mut builder = native_tls::TlsConnector::builder();
+
+    builder.danger_accept_invalid_certs(options.danger_accept_invalid_certs);
+
+    for pem in &options.root_certificates {
+        let certificate = native_tls::Certificate::from_pem(pem)
+            .map_err(|error| LavalinkNodeError::Tls(error.to_string()))?;
+
+        builder.add_root_certificate(certificate);
+    }
+
+    let connector = builder
+        .build()
+        .map_err(|error| LavalinkNodeError::Tls(error.to_string()))?;
+
+    Ok(Connector::NativeTls(connector))
+}
+
+/// Builds a websocket connector that follows the TLS options of a node
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+pub fn websocket_connector(_: &TlsOptions) -> Result<Connector, LavalinkNodeError> {
+    Ok(Connector::Plain)
+}
+
+#[cfg(feature = "rustls")]
+mod danger {
+    use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+    use rustls::crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature};
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use rustls::{DigitallySignedStruct, Error, SignatureScheme};
+    use std::sync::Arc;
+
+    /// Certificate verifier that trusts every certificate, but still checks handshake signatures
+    #[derive(Debug)]
+    pub struct NoCertificateVerification(pub Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for NoCertificateVerification {
+        fn verify_server_cert(
+            &self,
+            _: &CertificateDer<'_>,
+            _: &[CertificateDer<'_>],
+            _: &ServerName<'_>,
+            _: &[u8],
+            _: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use futures::future::BoxFuture;
+use std::io::{Error as IoError, ErrorKind};
+use std::result::Result;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Bytes;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::header::CONTENT_TYPE;
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderValue, Method, StatusCode};
+
+use crate::model::error::LavalinkRestError;
+
+/// Request a [`crate::node::rest::Rest`] sends through its transport
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: Method,
+    /// Full url of the request, query included
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Creates a request without headers or body
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// Sets a json body, along with its content type
+    pub fn json(mut self, body: String) -> Self {
+        self.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.body = Some(body);
+        self
+    }
+}
+
+/// Response a transport got back for a request
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub body: Bytes,
+}
+
+/// Sends the REST requests of every node
+///
+/// Implement this to use your own HTTP stack (e.g. hyper with a custom connector, or a test double).
+/// Custom transports handle TLS themselves, the TLS options of nodes only apply to websockets with them
+pub trait HttpTransport: Send + Sync {
+    /// Sends a request, returning the response whatever its status is
+    fn execute(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<HttpResponse, LavalinkRestError>>;
+}
+
+/// Transport backed by a reqwest client, used by default
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    /// Creates a transport that sends the requests with this client
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpTransport for ReqwestTransport {
+    fn execute(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<HttpResponse, LavalinkRestError>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(request.method, &request.url)
+                .headers(request.headers);
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+            let status = response.status();
+
+            Ok(HttpResponse {
+                status,
+                body: response.bytes().await?,
+            })
+        })
+    }
+}
+
+/// Stream a websocket runs on
+pub trait WsIo: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> WsIo for T {}
+
+/// Opens the streams the websockets of every node run on
+///
+/// Implement this to reach nodes through something else than TCP (e.g. unix sockets or an in-memory test transport).
+/// Anchorage still runs the websocket handshake, redirects included, on the streams this opens
+pub trait WsTransport: Send + Sync {
+    /// Opens a stream to the host of this handshake request
+    fn connect<'a>(&'a self, request: &'a Request)
+    -> BoxFuture<'a, Result<Box<dyn WsIo>, IoError>>;
+
+    /// If wss urls get wrapped with TLS following the TLS options of the node. Return false if the stream is already encrypted
+    fn wraps_tls(&self) -> bool {
+        true
+    }
+}
+
+/// Transport that opens plain TCP streams, used by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TcpTransport;
+
+impl WsTransport for TcpTransport {
+    fn connect<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> BoxFuture<'a, Result<Box<dyn WsIo>, IoError>> {
+        Box::pin(async move {
+            let uri = request.uri();
+
+            let Some(host) = uri.host() else {
+                return Err(IoError::new(ErrorKind::InvalidInput, "Url has no host"));
+            };
+
+            let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                Some("wss") => 443,
+                _ => 80,
+            });
+
+            // hosts of ipv6 urls keep their brackets
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+
+            let stream = TcpStream::connect((host, port)).await?;
+
+            stream.set_nodelay(true)?;
+
+            Ok(Box::new(stream) as Box<dyn WsIo>)
+        })
+    }
+}
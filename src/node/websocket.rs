@@ -1,74 +1,309 @@
 use flume::{Receiver as FlumeReceiver, Sender as FlumeSender, unbounded};
-use futures::stream::StreamExt;
+use futures::SinkExt;
+use futures::stream::{SplitSink, SplitStream, StreamExt};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 use std::{result::Result, time::Duration};
-use tokio::net::TcpStream;
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+use tokio_tungstenite::MaybeTlsStream;
 use tokio_tungstenite::tungstenite::Error as TungsteniteError;
+use tokio_tungstenite::tungstenite::http::header::{
+    CONNECTION, HOST, LOCATION, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_VERSION, UPGRADE, USER_AGENT,
+};
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
 use tokio_tungstenite::tungstenite::{Message, handshake::client::Request};
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
+use tokio_tungstenite::{Connector, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+use url::Url;
 
 use crate::model::error::LavalinkNodeError;
-use crate::model::node::LavalinkMessage;
+use crate::model::node::{ConnectionStats, LavalinkMessage};
+use crate::node::json;
+#[cfg(feature = "debug-recorder")]
+use crate::node::recorder::TrafficRecorder;
+use crate::node::transport::{WsIo, WsTransport};
+
+/// Maximum amount of redirects followed during the websocket handshake
+const MAX_REDIRECTS: usize = 5;
+
+/// Close codes that mean the node rejected the credentials, so reconnecting with them is pointless
+const AUTH_CLOSE_CODES: [u16; 3] = [4001, 4003, 4004];
+
+/// Shortest time the rates of a connection are measured over
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(1);
+
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+type WebSocket = WebSocketStream<MaybeTlsStream<Box<dyn WsIo>>>;
+
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+type WebSocket = WebSocketStream<Box<dyn WsIo>>;
 
 /// Internal websocket handler around WebsocketStream from tokio_tungstenite
+///
+/// permessage-deflate is not negotiated, as tungstenite does not implement the extension yet.
+/// Advertising it on the handshake would make the node send compressed frames this stream can't read
 pub struct ConnectionManager {
-    pub stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    pub stream: SplitStream<WebSocket>,
+    throughput: Arc<Mutex<Throughput>>,
+    #[cfg(feature = "debug-recorder")]
+    recorder: Option<Arc<TrafficRecorder>>,
 }
 
 impl ConnectionManager {
-    pub async fn new(request: Request) -> Result<Self, LavalinkNodeError> {
-        let (stream, _) = connect_async(request).await?;
+    /// Connects to the node, returns the manager that reads and the sink that writes to it
+    pub async fn new(
+        mut request: Request,
+        transport: &dyn WsTransport,
+        connector: Option<Connector>,
+    ) -> Result<(Self, SplitSink<WebSocket, Message>), LavalinkNodeError> {
+        let mut redirects = 0;
+
+        loop {
+            let uri = request.uri().to_string();
+            let headers = request.headers().clone();
+
+            let error = match Self::handshake(request, transport, connector.clone()).await {
+                Ok(stream) => {
+                    let (sink, stream) = stream.split();
+                    let manager = Self {
+                        stream,
+                        throughput: Arc::default(),
+                        #[cfg(feature = "debug-recorder")]
+                        recorder: None,
+                    };
+
+                    return Ok((manager, sink));
+                }
+                Err(error) => error,
+            };
+
+            if let TungsteniteError::Http(response) = &error
+                && matches!(response.status().as_u16(), 401 | 403)
+            {
+                return Err(LavalinkNodeError::InvalidCredentials(
+                    response.status().as_u16(),
+                ));
+            }
+
+            let location = match &error {
+                TungsteniteError::Http(response) if response.status().is_redirection() => response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string),
+                _ => None,
+            };
+
+            let Some(location) = location else {
+                return Err(error.into());
+            };
+
+            if redirects >= MAX_REDIRECTS {
+                return Err(LavalinkNodeError::TooManyRedirects(MAX_REDIRECTS));
+            }
+
+            redirects += 1;
+
+            let (origin, target) = Self::resolve_redirect(&uri, &location)?;
 
-        Ok(Self { stream })
+            tracing::debug!("Websocket handshake redirected to {}", target);
+
+            // re-issue the upgrade on the new location
+            request = Request::builder()
+                .method("GET")
+                .uri(target.as_str())
+                .body(())?;
+
+            *request.headers_mut() = Self::redirect_headers(&headers, &origin, &target)?;
+        }
     }
 
-    pub async fn get_message(&mut self) -> Result<Option<LavalinkMessage>, TungsteniteError> {
-        let Some(result) = self.stream.next().await else {
-            return Err(TungsteniteError::AlreadyClosed);
+    /// Headers of the upgrade sent to the location of a redirect
+    /// # Leaving the host drops the credentials, session and every custom header, they are only meant for the configured node
+    fn redirect_headers(
+        headers: &HeaderMap,
+        origin: &Url,
+        target: &Url,
+    ) -> Result<HeaderMap, LavalinkNodeError> {
+        let mut redirected = match origin.authority() == target.authority() {
+            true => headers.clone(),
+            false => {
+                let mut kept = HeaderMap::new();
+
+                // only what the upgrade itself needs
+                for name in [
+                    CONNECTION,
+                    UPGRADE,
+                    SEC_WEBSOCKET_KEY,
+                    SEC_WEBSOCKET_VERSION,
+                    USER_AGENT,
+                ] {
+                    if let Some(value) = headers.get(&name) {
+                        kept.insert(name, value.clone());
+                    }
+                }
+
+                kept
+            }
         };
 
-        let result = match result {
-            Ok(message) => message,
-            Err(error) => return Err(error),
+        redirected.insert(HOST, HeaderValue::from_str(target.authority())?);
+
+        Ok(redirected)
+    }
+
+    /// Resolves the location of a redirect against the url that was requested, returning both
+    /// # Redirects from wss to ws are rejected, they would send the credentials unencrypted
+    fn resolve_redirect(uri: &str, location: &str) -> Result<(Url, Url), LavalinkNodeError> {
+        let invalid = |_| LavalinkNodeError::InvalidRedirect(location.to_string());
+
+        let origin = Url::parse(uri).map_err(invalid)?;
+        let mut target = origin.join(location).map_err(invalid)?;
+
+        let scheme = match target.scheme() {
+            "http" | "ws" => "ws",
+            "https" | "wss" => "wss",
+            _ => return Err(LavalinkNodeError::InvalidRedirect(location.to_string())),
         };
 
-        let string = match result {
-            Message::Text(string) => string,
-            Message::Close(_) => return Err(TungsteniteError::ConnectionClosed),
+        if origin.scheme() == "wss" && scheme == "ws" {
+            return Err(LavalinkNodeError::InvalidRedirect(location.to_string()));
+        }
+
+        target
+            .set_scheme(scheme)
+            .map_err(|_| LavalinkNodeError::InvalidRedirect(location.to_string()))?;
+
+        Ok((origin, target))
+    }
+
+    async fn handshake(
+        request: Request,
+        transport: &dyn WsTransport,
+        connector: Option<Connector>,
+    ) -> Result<WebSocket, TungsteniteError> {
+        let io = transport.connect(&request).await?;
+
+        #[cfg(any(feature = "rustls", feature = "native-tls"))]
+        let (stream, _) = {
+            // a plain connector skips wrapping streams the transport already encrypted
+            let connector = match transport.wraps_tls() {
+                true => connector,
+                false => Some(Connector::Plain),
+            };
+
+            tokio_tungstenite::client_async_tls_with_config(request, io, None, connector).await?
+        };
+
+        #[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+        let (stream, _) = {
+            let _ = connector;
+            tokio_tungstenite::client_async(request, io).await?
+        };
+
+        Ok(stream)
+    }
+
+    pub async fn get_message(&mut self) -> Result<Option<LavalinkMessage>, LavalinkNodeError> {
+        let Some(result) = self.stream.next().await else {
+            return Err(TungsteniteError::AlreadyClosed.into());
+        };
+
+        let message = result?;
+
+        self.throughput.lock().unwrap().record(message.len());
+
+        let data = match message {
+            Message::Text(string) => {
+                #[cfg(feature = "debug-recorder")]
+                if let Some(recorder) = &self.recorder {
+                    recorder.record_frame(string.as_str());
+                }
+
+                string.into()
+            }
+            Message::Close(frame) => return Err(Self::closed(frame)),
             _ => return Ok(None),
         };
 
-        let message = match serde_json::from_str::<LavalinkMessage>(&string) {
+        let message = match json::from_bytes::<LavalinkMessage>(data) {
             Ok(message) => message,
             _ => return Ok(None),
         };
 
         Ok(Some(message))
     }
+
+    /// Turns the close frame the node sent into an error, telling credential failures apart from the rest
+    fn closed(frame: Option<CloseFrame>) -> LavalinkNodeError {
+        let Some(frame) = frame else {
+            return TungsteniteError::ConnectionClosed.into();
+        };
+
+        let code = u16::from(frame.code);
+        let reason = frame.reason.as_str().to_string();
+
+        if AUTH_CLOSE_CODES.contains(&code) {
+            return LavalinkNodeError::AuthenticationFailed { code, reason };
+        }
+
+        LavalinkNodeError::Closed { code, reason }
+    }
 }
 
 /// Public facing wrapper around connection manager
 pub struct Connection {
     handle: Option<JoinHandle<()>>,
-    sender: FlumeSender<Result<Option<LavalinkMessage>, TungsteniteError>>,
+    sink: Option<SplitSink<WebSocket, Message>>,
+    sender: FlumeSender<Result<Option<LavalinkMessage>, LavalinkNodeError>>,
+    transport: Arc<dyn WsTransport>,
+    connector: Option<Connector>,
+    cancellation_token: CancellationToken,
+    throughput: Arc<Mutex<Throughput>>,
+    #[cfg(feature = "debug-recorder")]
+    recorder: Option<Arc<TrafficRecorder>>,
 }
 
 impl Connection {
-    pub fn new() -> (
+    pub fn new(
+        transport: Arc<dyn WsTransport>,
+        connector: Option<Connector>,
+        cancellation_token: CancellationToken,
+    ) -> (
         Self,
-        FlumeReceiver<Result<Option<LavalinkMessage>, TungsteniteError>>,
+        FlumeReceiver<Result<Option<LavalinkMessage>, LavalinkNodeError>>,
     ) {
-        let (sender, receiver) = unbounded::<Result<Option<LavalinkMessage>, TungsteniteError>>();
+        let (sender, receiver) = unbounded::<Result<Option<LavalinkMessage>, LavalinkNodeError>>();
 
         let connection = Self {
             handle: None,
+            sink: None,
             sender,
+            transport,
+            connector,
+            cancellation_token,
+            throughput: Arc::default(),
+            #[cfg(feature = "debug-recorder")]
+            recorder: None,
         };
 
         (connection, receiver)
     }
 
+    /// Records the raw frames of every following connection
+    #[cfg(feature = "debug-recorder")]
+    pub fn record(&mut self, recorder: Arc<TrafficRecorder>) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Counters of the traffic this connection received, shared with its readers
+    pub(crate) fn throughput(&self) -> Arc<Mutex<Throughput>> {
+        self.throughput.clone()
+    }
+
     pub fn available(&self) -> bool {
         self.handle
             .as_ref()
@@ -79,13 +314,29 @@ impl Connection {
     pub async fn connect(&mut self, request: Request) -> Result<(), LavalinkNodeError> {
         self.disconnect().await;
 
-        let mut manager = ConnectionManager::new(request).await?;
+        let (mut manager, sink) =
+            ConnectionManager::new(request, self.transport.as_ref(), self.connector.clone())
+                .await?;
+
+        // the counters outlive reconnects, so the totals cover the whole life of the node
+        manager.throughput = self.throughput.clone();
+
+        #[cfg(feature = "debug-recorder")]
+        {
+            manager.recorder = self.recorder.clone();
+        }
 
         let sender = self.sender.clone();
+        let cancellation_token = self.cancellation_token.clone();
 
         let handle = tokio::spawn(async move {
             loop {
-                match manager.get_message().await {
+                let result = tokio::select! {
+                    result = manager.get_message() => result,
+                    _ = cancellation_token.cancelled() => break,
+                };
+
+                match result {
                     Ok(message) => {
                         if sender.send_async(Ok(message)).await.is_err() {
                             break;
@@ -111,11 +362,30 @@ impl Connection {
         #[allow(clippy::let_underscore_future)]
         let _ = self.handle.insert(handle);
 
+        self.sink = Some(sink);
+
+        Ok(())
+    }
+
+    /// Writes a message to the node
+    pub async fn send(&mut self, message: Message) -> Result<(), LavalinkNodeError> {
+        if !self.available() {
+            return Err(LavalinkNodeError::NotConnected);
+        }
+
+        let Some(sink) = self.sink.as_mut() else {
+            return Err(LavalinkNodeError::NotConnected);
+        };
+
+        sink.send(message).await?;
+
         Ok(())
     }
 
     #[tracing::instrument(skip(self))]
     pub async fn disconnect(&mut self) {
+        self.sink = None;
+
         let Some(handle) = self.handle.take() else {
             return;
         };
@@ -132,3 +402,187 @@ impl Connection {
         );
     }
 }
+
+/// Counts the traffic a connection received, measuring its rates over windows of at least a second
+#[derive(Debug)]
+pub(crate) struct Throughput {
+    bytes_received: u64,
+    messages_received: u64,
+    last_message_at: Option<SystemTime>,
+    window_start: Instant,
+    window_bytes: u64,
+    window_messages: u64,
+    bytes_per_second: f64,
+    messages_per_second: f64,
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        Self {
+            bytes_received: 0,
+            messages_received: 0,
+            last_message_at: None,
+            window_start: Instant::now(),
+            window_bytes: 0,
+            window_messages: 0,
+            bytes_per_second: 0.0,
+            messages_per_second: 0.0,
+        }
+    }
+}
+
+impl Throughput {
+    /// Counts a received message
+    fn record(&mut self, bytes: usize) {
+        self.roll();
+
+        self.bytes_received += bytes as u64;
+        self.messages_received += 1;
+        self.window_bytes += bytes as u64;
+        self.window_messages += 1;
+        self.last_message_at = Some(SystemTime::now());
+    }
+
+    /// Gets the current totals and rates
+    pub(crate) fn stats(&mut self) -> ConnectionStats {
+        // rolling on read makes a stalled connection drop to zero instead of keeping its last rates
+        self.roll();
+
+        ConnectionStats {
+            bytes_per_second: self.bytes_per_second,
+            messages_per_second: self.messages_per_second,
+            bytes_received: self.bytes_received,
+            messages_received: self.messages_received,
+            last_message_at: self.last_message_at,
+        }
+    }
+
+    /// Turns the window into rates once it lasted long enough
+    fn roll(&mut self) {
+        let elapsed = self.window_start.elapsed();
+
+        if elapsed < THROUGHPUT_WINDOW {
+            return;
+        }
+
+        let seconds = elapsed.as_secs_f64();
+
+        self.bytes_per_second = self.window_bytes as f64 / seconds;
+        self.messages_per_second = self.window_messages as f64 / seconds;
+        self.window_start = Instant::now();
+        self.window_bytes = 0;
+        self.window_messages = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_tungstenite::tungstenite::http::header::{AUTHORIZATION, HOST};
+    use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderValue};
+
+    use super::ConnectionManager;
+    use crate::model::error::LavalinkNodeError;
+
+    /// Headers of an upgrade the way a node sends it
+    fn node_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        headers.insert(HOST, HeaderValue::from_static("lavalink:2333"));
+        headers.insert("Connection", HeaderValue::from_static("Upgrade"));
+        headers.insert("Upgrade", HeaderValue::from_static("websocket"));
+        headers.insert("Sec-WebSocket-Version", HeaderValue::from_static("13"));
+        headers.insert(
+            "Sec-WebSocket-Key",
+            HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="),
+        );
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("youshallnotpass"));
+        headers.insert("User-Id", HeaderValue::from_static("424137718961012737"));
+        headers.insert("Session-Id", HeaderValue::from_static("session"));
+        headers.insert("Client-Name", HeaderValue::from_static("Anchorage/1.0"));
+        headers.insert("X-Region", HeaderValue::from_static("eu"));
+
+        headers
+    }
+
+    #[test]
+    fn rejects_wss_to_ws_redirects() {
+        let result =
+            ConnectionManager::resolve_redirect("wss://lavalink:2333/v4/websocket", "ws://other/");
+
+        assert!(matches!(result, Err(LavalinkNodeError::InvalidRedirect(_))));
+    }
+
+    #[test]
+    fn resolves_relative_locations() {
+        let (origin, target) =
+            ConnectionManager::resolve_redirect("ws://lavalink:2333/v4/websocket", "/v5/websocket")
+                .unwrap();
+
+        assert_eq!(origin.as_str(), "ws://lavalink:2333/v4/websocket");
+        assert_eq!(target.as_str(), "ws://lavalink:2333/v5/websocket");
+    }
+
+    #[test]
+    fn maps_http_locations_to_websockets() {
+        let (_, target) = ConnectionManager::resolve_redirect(
+            "ws://lavalink:2333/v4/websocket",
+            "http://other:2333/v4/websocket",
+        )
+        .unwrap();
+        assert_eq!(target.as_str(), "ws://other:2333/v4/websocket");
+
+        let (_, target) = ConnectionManager::resolve_redirect(
+            "wss://lavalink/v4/websocket",
+            "https://other/v4/websocket",
+        )
+        .unwrap();
+        assert_eq!(target.as_str(), "wss://other/v4/websocket");
+    }
+
+    #[test]
+    fn strips_client_headers_across_hosts() {
+        let headers = node_headers();
+
+        let (origin, target) = ConnectionManager::resolve_redirect(
+            "ws://lavalink:2333/v4/websocket",
+            "ws://other:2333/v4/websocket",
+        )
+        .unwrap();
+
+        let redirected = ConnectionManager::redirect_headers(&headers, &origin, &target).unwrap();
+
+        for name in [
+            "Authorization",
+            "User-Id",
+            "Session-Id",
+            "Client-Name",
+            "X-Region",
+        ] {
+            assert!(!redirected.contains_key(name), "{name} was forwarded");
+        }
+
+        for name in [
+            "Connection",
+            "Upgrade",
+            "Sec-WebSocket-Version",
+            "Sec-WebSocket-Key",
+        ] {
+            assert_eq!(redirected.get(name), headers.get(name));
+        }
+
+        assert_eq!(redirected.get(HOST).unwrap(), "other:2333");
+    }
+
+    #[test]
+    fn keeps_client_headers_on_the_same_host() {
+        let headers = node_headers();
+
+        let (origin, target) =
+            ConnectionManager::resolve_redirect("ws://lavalink:2333/v4/websocket", "/v5/websocket")
+                .unwrap();
+
+        let redirected = ConnectionManager::redirect_headers(&headers, &origin, &target).unwrap();
+
+        assert_eq!(redirected, headers);
+    }
+}
@@ -0,0 +1,141 @@
+use flume::{Receiver as FlumeReceiver, Sender as FlumeSender};
+use tokio::sync::broadcast::error::RecvError as BroadcastRecvError;
+use tokio::sync::broadcast::{Receiver as BroadcastReceiver, Sender as BroadcastSender};
+use tokio::sync::mpsc::{
+    Receiver as TokioReceiver, Sender as TokioSender, UnboundedReceiver as TokioUnboundedReceiver,
+    UnboundedSender as TokioUnboundedSender,
+};
+
+use crate::model::player::EventType;
+
+/// Channel a player delivers its events through
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EventChannel {
+    /// Unbounded flume channel
+    #[default]
+    Flume,
+    /// Bounded flume channel, the node waits for room when it's full
+    FlumeBounded(usize),
+    /// Unbounded tokio mpsc channel
+    Tokio,
+    /// Bounded tokio mpsc channel, the node waits for room when it's full
+    TokioBounded(usize),
+    /// Tokio broadcast channel, subscribers that fall behind skip the oldest events
+    Broadcast(usize),
+}
+
+impl EventChannel {
+    /// Creates both ends of this channel
+    pub fn create(self) -> (EventSender, EventReceiver) {
+        match self {
+            EventChannel::Flume => {
+                let (sender, receiver) = flume::unbounded();
+                (EventSender::Flume(sender), EventReceiver::Flume(receiver))
+            }
+            EventChannel::FlumeBounded(capacity) => {
+                let (sender, receiver) = flume::bounded(capacity);
+                (EventSender::Flume(sender), EventReceiver::Flume(receiver))
+            }
+            EventChannel::Tokio => {
+                let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+                (
+                    EventSender::TokioUnbounded(sender),
+                    EventReceiver::TokioUnbounded(receiver),
+                )
+            }
+            EventChannel::TokioBounded(capacity) => {
+                let (sender, receiver) = tokio::sync::mpsc::channel(capacity.max(1));
+                (EventSender::Tokio(sender), EventReceiver::Tokio(receiver))
+            }
+            EventChannel::Broadcast(capacity) => {
+                let (sender, receiver) = tokio::sync::broadcast::channel(capacity.max(1));
+                (
+                    EventSender::Broadcast(sender),
+                    EventReceiver::Broadcast(receiver),
+                )
+            }
+        }
+    }
+}
+
+/// Sending half of a player event channel
+#[derive(Clone, Debug)]
+pub enum EventSender {
+    /// Flume channel, bounded or unbounded
+    Flume(FlumeSender<EventType>),
+    /// Bounded tokio mpsc channel
+    Tokio(TokioSender<EventType>),
+    /// Unbounded tokio mpsc channel
+    TokioUnbounded(TokioUnboundedSender<EventType>),
+    /// Tokio broadcast channel, slow receivers skip the oldest events
+    Broadcast(BroadcastSender<EventType>),
+}
+
+impl EventSender {
+    /// Sends an event, waiting for room on bounded channels. Returns false if nobody is listening
+    pub async fn send(&self, event: EventType) -> bool {
+        match self {
+            EventSender::Flume(sender) => sender.send_async(event).await.is_ok(),
+            EventSender::Tokio(sender) => sender.send(event).await.is_ok(),
+            EventSender::TokioUnbounded(sender) => sender.send(event).is_ok(),
+            EventSender::Broadcast(sender) => sender.send(event).is_ok(),
+        }
+    }
+
+    /// Sends an event without waiting, dropping it if a bounded channel is full
+    pub fn try_send(&self, event: EventType) -> bool {
+        match self {
+            EventSender::Flume(sender) => sender.try_send(event).is_ok(),
+            EventSender::Tokio(sender) => sender.try_send(event).is_ok(),
+            EventSender::TokioUnbounded(sender) => sender.send(event).is_ok(),
+            EventSender::Broadcast(sender) => sender.send(event).is_ok(),
+        }
+    }
+
+    /// If both senders belong to the same channel
+    pub fn same_channel(&self, other: &EventSender) -> bool {
+        match (self, other) {
+            (EventSender::Flume(a), EventSender::Flume(b)) => a.same_channel(b),
+            (EventSender::Tokio(a), EventSender::Tokio(b)) => a.same_channel(b),
+            (EventSender::TokioUnbounded(a), EventSender::TokioUnbounded(b)) => a.same_channel(b),
+            (EventSender::Broadcast(a), EventSender::Broadcast(b)) => a.same_channel(b),
+            _ => false,
+        }
+    }
+}
+
+/// Receiving half of a player event channel
+#[derive(Debug)]
+pub enum EventReceiver {
+    /// Flume channel, bounded or unbounded
+    Flume(FlumeReceiver<EventType>),
+    /// Bounded tokio mpsc channel
+    Tokio(TokioReceiver<EventType>),
+    /// Unbounded tokio mpsc channel
+    TokioUnbounded(TokioUnboundedReceiver<EventType>),
+    /// Tokio broadcast channel, skips the events it lagged behind on
+    Broadcast(BroadcastReceiver<EventType>),
+}
+
+impl EventReceiver {
+    /// Waits for the next event, returns none once the player is gone
+    pub async fn recv(&mut self) -> Option<EventType> {
+        match self {
+            EventReceiver::Flume(receiver) => receiver.recv_async().await.ok(),
+            EventReceiver::Tokio(receiver) => receiver.recv().await,
+            EventReceiver::TokioUnbounded(receiver) => receiver.recv().await,
+            EventReceiver::Broadcast(receiver) => loop {
+                match receiver.recv().await {
+                    Ok(event) => break Some(event),
+                    Err(BroadcastRecvError::Lagged(skipped)) => {
+                        tracing::debug!(
+                            "Player events receiver lagged, {} event(s) skipped",
+                            skipped
+                        );
+                    }
+                    Err(BroadcastRecvError::Closed) => break None,
+                }
+            },
+        }
+    }
+}
@@ -1,43 +1,274 @@
-use flume::{Receiver as FlumeReceiver, Sender as FlumeSender, unbounded};
+pub mod events;
+
 use serde_json::Value;
+use std::ops::Range;
 use std::result::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 
 use crate::model::anchorage::{ConnectionOptions, PlayerOptions};
-use crate::model::error::LavalinkPlayerError;
+use crate::model::error::{LavalinkPlayerError, QueueError};
+use crate::model::id::GuildId;
 use crate::model::player::{
-    EventType, LavalinkFilters, LavalinkPlayer, LavalinkPlayerOptions, LavalinkVoice,
+    ChannelMix, DestroyReason, EventType, LavalinkFilters, LavalinkPlayer, LavalinkPlayerOptions,
+    LavalinkPlayerState, LavalinkVoice, LowPass, MAX_VOLUME, PlayerSnapshot, Track, TrackPlaylist,
     UpdatePlayerTrack,
 };
 use crate::node::client::Node;
+use crate::player::events::{EventReceiver, EventSender};
+use crate::queue::{LoopMode, Queue, QueueOptions};
+
+/// Interval between each volume change of a fade
+const FADE_STEP: Duration = Duration::from_millis(250);
 
 /// A player instance
+#[derive(Clone)]
 pub struct Player {
     /// GuildId for this player
-    pub guild_id: u64,
+    pub guild_id: GuildId,
     /// Node where this player is
-    node: Node,
+    location: PlayerLocation,
+    /// Destroys the player once the last handle is dropped, if enabled
+    destroy_guard: Option<Arc<DestroyGuard>>,
+    /// Cancels the fade that is running on this player, if there is one
+    fade: Arc<Mutex<Option<CancellationToken>>>,
+    /// Highest volume this player can be set to
+    max_volume: u32,
+    /// Rejects filters the node has disabled when updating filters
+    validate_filters: bool,
+}
+
+/// Node a player is on, shared by every handle of the player so they follow it when it moves
+#[derive(Clone, Debug)]
+pub(crate) struct PlayerLocation {
+    node: Arc<RwLock<Node>>,
+    resume_grace: Option<Duration>,
+    /// If the bot joined deafened, as the last connection said
+    self_deaf: Arc<AtomicBool>,
+    /// If the bot joined muted, as the last connection said
+    self_mute: Arc<AtomicBool>,
+}
+
+impl PlayerLocation {
+    pub(crate) fn new(node: Node, resume_grace: Option<Duration>) -> Self {
+        Self {
+            node: Arc::new(RwLock::new(node)),
+            resume_grace,
+            self_deaf: Arc::new(AtomicBool::new(true)),
+            self_mute: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Node the player is currently on
+    pub(crate) fn node(&self) -> Node {
+        self.node.read().unwrap().clone()
+    }
+
+    /// Points every handle of the player to another node
+    pub(crate) fn set(&self, node: Node) {
+        *self.node.write().unwrap() = node;
+    }
+
+    /// How far before its last known position the player resumes after moving, none if it doesn't
+    pub(crate) fn resume_grace(&self) -> Option<Duration> {
+        self.resume_grace
+    }
+
+    /// Remembers the deaf and mute state of the connection the player joined with
+    pub(crate) fn set_voice_state(&self, self_deaf: bool, self_mute: bool) {
+        self.self_deaf.store(self_deaf, Ordering::Relaxed);
+        self.self_mute.store(self_mute, Ordering::Relaxed);
+    }
+
+    /// Copies the deaf and mute state of the player into a snapshot of it
+    pub(crate) fn fill_snapshot(&self, snapshot: &mut PlayerSnapshot) {
+        snapshot.self_deaf = self.self_deaf.load(Ordering::Relaxed);
+        snapshot.self_mute = self.self_mute.load(Ordering::Relaxed);
+    }
+}
+
+/// Position in milliseconds a player resumes at, from a state lavalink sent minus a grace. None if nothing plays
+pub(crate) fn resume_position(
+    player: &LavalinkPlayer,
+    state: &LavalinkPlayerState,
+    grace: Duration,
+) -> Option<u64> {
+    let track = player.track.as_ref()?;
+
+    let position = if player.paused {
+        state.position
+    } else {
+        state.interpolated_position()
+    };
+
+    Some(
+        position
+            .saturating_sub(duration_to_millis(grace))
+            .min(track.info.length),
+    )
+}
+
+/// Handle of a fade running in the background
+pub struct Fade {
+    token: CancellationToken,
+    handle: JoinHandle<Result<bool, LavalinkPlayerError>>,
+}
+
+impl Fade {
+    /// Stops the fade, leaving the volume where it currently is
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Waits for the fade to end, returns false if it was cancelled before finishing
+    pub async fn wait(self) -> Result<bool, LavalinkPlayerError> {
+        self.handle.await?
+    }
+}
+
+/// Destroys a player on lavalink when dropped, unless it was already destroyed
+struct DestroyGuard {
+    guild_id: GuildId,
+    location: PlayerLocation,
+    events_sender: EventSender,
+    destroyed: AtomicBool,
+}
+
+impl Drop for DestroyGuard {
+    fn drop(&mut self) {
+        if self.destroyed.load(Ordering::Acquire) {
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let guild_id = self.guild_id;
+        let node = self.location.node();
+        let events_sender = self.events_sender.clone();
+
+        runtime.spawn(async move {
+            // the player could be destroyed already, or replaced by a newer player of this guild
+            let owned = node
+                .events_sender
+                .read_async(&guild_id, |_, sender| sender.same_channel(&events_sender))
+                .await
+                .unwrap_or(false);
+
+            if !owned {
+                return;
+            }
+
+            if let Err(error) = node.rest.destroy_player(guild_id).await {
+                tracing::warn!(
+                    "Failed to destroy dropped player of guild {} => {:?}",
+                    guild_id,
+                    error
+                );
+            }
+
+            node.events_sender.remove_async(&guild_id).await;
+            node.voices.remove_async(&guild_id).await;
+            node.now_playing.remove_async(&guild_id).await;
+            node.locations.remove_async(&guild_id).await;
+
+            if let Err(error) = node.queues.remove(guild_id).await {
+                tracing::warn!(
+                    "Failed to remove the queue of dropped player of guild {} => {:?}",
+                    guild_id,
+                    error
+                );
+            }
+
+            events_sender
+                .send(EventType::Destroyed(DestroyReason::Requested))
+                .await;
+        });
+    }
 }
 
 impl Player {
     /// Creates a new player
     pub async fn new(
         options: PlayerOptions,
-    ) -> Result<(Self, FlumeSender<EventType>, FlumeReceiver<EventType>), LavalinkPlayerError> {
-        let (events_sender, events_receiver) = unbounded::<EventType>();
+    ) -> Result<(Self, EventSender, EventReceiver), LavalinkPlayerError> {
+        let max_volume = options.max_volume.unwrap_or(MAX_VOLUME).min(MAX_VOLUME);
+
+        if let Some(volume) = options.volume {
+            Self::check_volume(volume, max_volume)?;
+        }
+
+        let (events_sender, events_receiver) = options.events.create();
+
+        let location = PlayerLocation::new(options.node.clone(), options.resume_grace);
+
+        options
+            .node
+            .locations
+            .upsert_async(options.guild_id, location.clone())
+            .await;
+
+        let destroy_guard = options.destroy_on_drop.then(|| {
+            Arc::new(DestroyGuard {
+                guild_id: options.guild_id,
+                location: location.clone(),
+                events_sender: events_sender.clone(),
+                destroyed: AtomicBool::new(false),
+            })
+        });
 
         let player = Self {
             guild_id: options.guild_id,
-            node: options.node,
+            location,
+            destroy_guard,
+            fade: Arc::new(Mutex::new(None)),
+            max_volume,
+            validate_filters: options.validate_filters.unwrap_or(false),
         };
 
-        player.update_connection(options.connection).await?;
+        // sends the initial state along with the voice data, so the player starts with everything set at once
+        let initial = LavalinkPlayerOptions {
+            volume: options.volume,
+            paused: options.paused,
+            filters: options.filters,
+            ..Default::default()
+        };
+
+        player.connect(options.connection, initial).await?;
 
         Ok((player, events_sender, events_receiver))
     }
 
+    /// Gets the node this player is currently on
+    pub fn node(&self) -> Node {
+        self.location.node()
+    }
+
     /// Gets the data of this player from lavalink
     pub async fn get_data(&self) -> Result<LavalinkPlayer, LavalinkPlayerError> {
-        Ok(self.node.rest.get_player(self.guild_id).await?)
+        Ok(self.node().rest.get_player(self.guild_id).await?)
+    }
+
+    /// Saves the state of this player, so it can be restored later on any node
+    pub async fn snapshot(&self) -> Result<PlayerSnapshot, LavalinkPlayerError> {
+        let mut snapshot: PlayerSnapshot = self.get_data().await?.into();
+
+        self.location.fill_snapshot(&mut snapshot);
+
+        Ok(snapshot)
+    }
+
+    /// Applies the playback state of a snapshot to this player
+    pub(crate) async fn restore(
+        &self,
+        snapshot: PlayerSnapshot,
+    ) -> Result<(), LavalinkPlayerError> {
+        self.send_update_player(false, snapshot.into()).await
     }
 
     /// Plays a track
@@ -56,6 +287,35 @@ impl Player {
         Ok(())
     }
 
+    /// Plays a track along with its user data (e.g. its requester), which lavalink sends back on its events
+    pub async fn play_track(&self, track: &Track) -> Result<(), LavalinkPlayerError> {
+        let options = LavalinkPlayerOptions {
+            track: Some(track.into()),
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await
+    }
+
+    /// Gets the track this player is playing, as last reported by lavalink
+    pub async fn now_playing(&self) -> Option<Track> {
+        self.node()
+            .now_playing
+            .read_async(&self.guild_id, |_, track| track.clone())
+            .await
+    }
+
+    /// Plays the current track again from the start, without fetching the player from lavalink
+    pub async fn restart(&self) -> Result<Track, LavalinkPlayerError> {
+        let Some(track) = self.now_playing().await else {
+            return Err(LavalinkPlayerError::NothingPlaying);
+        };
+
+        self.play_track(&track).await?;
+
+        Ok(track)
+    }
+
     /// Stops the current playback
     pub async fn stop(&self) -> Result<(), LavalinkPlayerError> {
         let mut options: LavalinkPlayerOptions = Default::default();
@@ -72,7 +332,323 @@ impl Player {
 
     /// Destroys the player on lavalink
     pub async fn destroy(&self) -> Result<(), LavalinkPlayerError> {
-        self.node.rest.destroy_player(self.guild_id).await?;
+        let node = self.node();
+
+        node.rest.destroy_player(self.guild_id).await?;
+
+        node.voices.remove_async(&self.guild_id).await;
+        node.now_playing.remove_async(&self.guild_id).await;
+        node.locations.remove_async(&self.guild_id).await;
+        node.queues.remove(self.guild_id).await?;
+
+        if let Some(guard) = &self.destroy_guard {
+            guard.destroyed.store(true, Ordering::Release);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the queue of this player
+    pub async fn get_queue(&self) -> Result<Queue, LavalinkPlayerError> {
+        Ok(self.node().queues.get(self.guild_id).await?)
+    }
+
+    /// Adds a track to the queue, it plays once the tracks before it end
+    pub async fn enqueue(&self, track: Track) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.push(track))
+            .await??;
+
+        Ok(())
+    }
+
+    /// Adds a track at a position in the queue, 0 being the next one to play
+    pub async fn insert_track(
+        &self,
+        index: usize,
+        track: Track,
+    ) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.insert_at(index, track))
+            .await??;
+
+        Ok(())
+    }
+
+    /// Moves a queued track to another position
+    pub async fn move_track(&self, from: usize, to: usize) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.move_track(from, to))
+            .await??;
+
+        Ok(())
+    }
+
+    /// Swaps the positions of two queued tracks
+    pub async fn swap_tracks(&self, a: usize, b: usize) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.swap(a, b))
+            .await??;
+
+        Ok(())
+    }
+
+    /// Removes a range of queued tracks, and returns them
+    pub async fn remove_tracks(
+        &self,
+        range: Range<usize>,
+    ) -> Result<Vec<Track>, LavalinkPlayerError> {
+        Ok(self
+            .node()
+            .queues
+            .update(self.guild_id, |queue| queue.remove_range(range))
+            .await??)
+    }
+
+    /// Skips ahead to a queued track and plays it, see [`Queue::jump_to`] for what happens to the tracks before it
+    pub async fn jump_to(&self, index: usize) -> Result<Track, LavalinkPlayerError> {
+        let track = self
+            .node()
+            .queues
+            .update(self.guild_id, |queue| queue.jump_to(index))
+            .await??;
+
+        self.play_track(&track).await?;
+
+        Ok(track)
+    }
+
+    /// Changes what plays once the current track finishes
+    pub async fn set_loop_mode(&self, mode: LoopMode) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.loop_mode = mode)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Saves the position of the current track in the queue, then exports it as json (e.g. to save it as a playlist)
+    pub async fn export_queue(&self) -> Result<String, LavalinkPlayerError> {
+        let data = self.get_data().await?;
+
+        let queue = self
+            .node()
+            .queues
+            .update(self.guild_id, |queue| {
+                if data.track.is_some() {
+                    queue.position = data.state.position;
+                }
+
+                queue.clone()
+            })
+            .await?;
+
+        Ok(queue.to_json()?)
+    }
+
+    /// Changes the policies the queue of this player follows when tracks are added
+    pub async fn set_queue_options(
+        &self,
+        options: QueueOptions,
+    ) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.options = options)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Plays the next track in the queue, returns none if the queue is empty
+    pub async fn play_next(&self) -> Result<Option<Track>, LavalinkPlayerError> {
+        let Some(track) = self
+            .node()
+            .queues
+            .update(self.guild_id, Queue::advance)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        self.play_track(&track).await?;
+
+        Ok(Some(track))
+    }
+
+    /// Skips the current track, playing the next queued one or the one autoplay picks
+    ///
+    /// Stops the player if autoplay picked none. Returns the skipped track, none if nothing was playing
+    pub async fn skip(&self) -> Result<Option<Track>, LavalinkPlayerError> {
+        let queues = self.node().queues;
+        let queue = queues.get(self.guild_id).await?;
+
+        if queue.is_empty() && !queue.autoplay {
+            return Err(QueueError::Empty.into());
+        }
+
+        let next = queues.next_track(self.guild_id).await?;
+
+        match &next {
+            Some(track) => self.play_track(track).await?,
+            None => self.stop().await?,
+        }
+
+        Ok(queue.current)
+    }
+
+    /// Plays the track that played before the current one again, queueing the current one next
+    pub async fn previous(&self) -> Result<Track, LavalinkPlayerError> {
+        let Some(track) = self
+            .node()
+            .queues
+            .update(self.guild_id, Queue::previous)
+            .await?
+        else {
+            return Err(QueueError::HistoryEmpty.into());
+        };
+
+        self.play_track(&track).await?;
+
+        Ok(track)
+    }
+
+    /// Plays a playlist from its selected track, then queues the tracks after it
+    ///
+    /// Tracks the queue rejects as duplicates are skipped.
+    /// Returns the track that started playing, none if the playlist is empty
+    pub async fn play_playlist(
+        &self,
+        playlist: &TrackPlaylist,
+    ) -> Result<Option<Track>, LavalinkPlayerError> {
+        let mut tracks = playlist.tracks.iter().skip(playlist.start_index()).cloned();
+
+        let Some(track) = tracks.next() else {
+            return Ok(None);
+        };
+
+        let queued = self
+            .node()
+            .queues
+            .update(self.guild_id, |queue| {
+                queue.current = Some(track.clone());
+
+                tracks
+                    .map(|track| queue.push(track))
+                    .filter(Result::is_ok)
+                    .count()
+            })
+            .await?;
+
+        self.play_track(&track).await?;
+
+        self.send_event(EventType::PlaylistQueued {
+            name: playlist.info.name.clone(),
+            queued,
+            total: playlist.tracks.len(),
+        })
+        .await;
+
+        Ok(Some(track))
+    }
+
+    /// Enables or disables autoplay once the queue of this player runs out
+    pub async fn set_autoplay(&self, enabled: bool) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, |queue| queue.autoplay = enabled)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fades the current track out, plays the next queued track, then fades back in
+    ///
+    /// Half of the duration is spent on each fade. Stops the player if the queue is empty
+    pub async fn fade_to_next(
+        &self,
+        duration: Duration,
+    ) -> Result<Option<Track>, LavalinkPlayerError> {
+        let token = self.begin_fade();
+        let filters = self.get_data().await?.filters;
+        let volume = filters.volume.unwrap_or(1.0);
+
+        if !self
+            .ramp_volume(&filters, volume, 0.0, duration / 2, &token)
+            .await?
+        {
+            return Ok(None);
+        }
+
+        let track = self.node().queues.next_track(self.guild_id).await?;
+
+        match &track {
+            Some(track) => self.play_track(track).await?,
+            None => self.stop().await?,
+        }
+
+        self.ramp_volume(&filters, 0.0, volume, duration / 2, &token)
+            .await?;
+
+        Ok(track)
+    }
+
+    /// Fades the filter volume down to silence, then stops the player and restores the volume at once
+    ///
+    /// Returns false if the fade was cancelled, leaving the player playing
+    pub async fn stop_with_fadeout(&self, duration: Duration) -> Result<bool, LavalinkPlayerError> {
+        let token = self.begin_fade();
+        let filters = self.get_data().await?.filters;
+        let volume = filters.volume.unwrap_or(1.0);
+
+        if !self
+            .ramp_volume(&filters, volume, 0.0, duration, &token)
+            .await?
+        {
+            return Ok(false);
+        }
+
+        let options = LavalinkPlayerOptions {
+            track: Some(UpdatePlayerTrack {
+                encoded: Some(Value::Null),
+                ..Default::default()
+            }),
+            filters: Some(filters),
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await?;
+
+        Ok(true)
+    }
+
+    /// Fades the filter volume from silence up to full volume in the background
+    pub fn fade_in(&self, duration: Duration) -> Fade {
+        self.spawn_fade(Some(0.0), 1.0, duration)
+    }
+
+    /// Fades the filter volume from where it is down to silence in the background
+    pub fn fade_out(&self, duration: Duration) -> Fade {
+        self.spawn_fade(None, 0.0, duration)
+    }
+
+    /// Cancels the fade that is running on this player, if there is one
+    pub fn cancel_fade(&self) {
+        if let Some(token) = self.fade.lock().unwrap().take() {
+            token.cancel();
+        }
+    }
+
+    /// Removes every track in the queue
+    pub async fn clear_queue(&self) -> Result<(), LavalinkPlayerError> {
+        self.node()
+            .queues
+            .update(self.guild_id, Queue::clear)
+            .await?;
 
         Ok(())
     }
@@ -90,8 +666,15 @@ impl Player {
         Ok(())
     }
 
-    /// Changes the player volume
+    /// Highest volume this player can be set to
+    pub fn max_volume(&self) -> u32 {
+        self.max_volume
+    }
+
+    /// Changes the player volume, which must not be higher than the max volume of this player
     pub async fn update_volume(&self, volume: u32) -> Result<(), LavalinkPlayerError> {
+        Self::check_volume(volume, self.max_volume)?;
+
         let mut options: LavalinkPlayerOptions = Default::default();
 
         let _ = options.volume.insert(volume);
@@ -101,11 +684,27 @@ impl Player {
         Ok(())
     }
 
+    /// Gets the position of the current track
+    pub async fn get_position(&self) -> Result<Duration, LavalinkPlayerError> {
+        Ok(self.get_data().await?.state.position())
+    }
+
     /// Seeks the player
-    pub async fn update_position(&mut self, position: u32) -> Result<(), LavalinkPlayerError> {
+    pub async fn update_position(&self, position: Duration) -> Result<(), LavalinkPlayerError> {
         let mut options: LavalinkPlayerOptions = Default::default();
 
-        let _ = options.position.insert(position);
+        let _ = options.position.insert(duration_to_millis(position));
+
+        self.send_update_player(false, options).await?;
+
+        Ok(())
+    }
+
+    /// Sets where the current track stops playing
+    pub async fn update_end_time(&self, end_time: Duration) -> Result<(), LavalinkPlayerError> {
+        let mut options: LavalinkPlayerOptions = Default::default();
+
+        let _ = options.end_time.insert(duration_to_millis(end_time));
 
         self.send_update_player(false, options).await?;
 
@@ -117,6 +716,10 @@ impl Player {
         &self,
         mut filters: LavalinkFilters,
     ) -> Result<(), LavalinkPlayerError> {
+        if self.validate_filters {
+            self.check_filters(&filters).await?;
+        }
+
         let data = self.get_data().await?;
 
         filters.merge(data.filters.clone());
@@ -130,6 +733,66 @@ impl Player {
         Ok(())
     }
 
+    /// Mixes the channels of the player into mono, or restores them back to stereo
+    pub async fn set_mono(&self, enabled: bool) -> Result<(), LavalinkPlayerError> {
+        let mut filters = self.get_data().await?.filters;
+
+        filters.channel_mix = enabled.then(ChannelMix::mono);
+
+        let options = LavalinkPlayerOptions {
+            filters: Some(filters),
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await
+    }
+
+    /// Suppresses higher frequencies with a smoothing factor, which must be higher than 1.0
+    pub async fn set_lowpass(&self, smoothing: f64) -> Result<(), LavalinkPlayerError> {
+        if smoothing.is_nan() || smoothing <= 1.0 {
+            return Err(LavalinkPlayerError::InvalidSmoothing(smoothing));
+        }
+
+        let mut filters = self.get_data().await?.filters;
+
+        // merging keeps the smoothing already set, so the low pass is replaced as a whole
+        filters.low_pass = Some(LowPass {
+            smoothing: Some(smoothing),
+        });
+
+        if self.validate_filters {
+            self.check_filters(&filters).await?;
+        }
+
+        let options = LavalinkPlayerOptions {
+            filters: Some(filters),
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await
+    }
+
+    /// Applies a registered preset, setting its filters and volume at once
+    pub async fn apply_preset(&self, name: &str) -> Result<(), LavalinkPlayerError> {
+        let node = self.node();
+
+        let Some(preset) = node.presets.get(name).await else {
+            return Err(LavalinkPlayerError::PresetNotFound(name.to_string()));
+        };
+
+        if let Some(volume) = preset.volume {
+            Self::check_volume(volume, self.max_volume)?;
+        }
+
+        let options = LavalinkPlayerOptions {
+            filters: preset.filters,
+            volume: preset.volume,
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await
+    }
+
     /// Clears the filters applied in the player
     pub async fn clear_filters(&self) -> Result<(), LavalinkPlayerError> {
         let filters = Default::default();
@@ -143,11 +806,61 @@ impl Player {
         Ok(())
     }
 
-    /// Updates the connection info of the player
+    /// Updates the connection info of the player, validating it first
     pub async fn update_connection(
+        &self,
+        mut connection: ConnectionOptions,
+    ) -> Result<(), LavalinkPlayerError> {
+        connection.validate()?;
+
+        self.connect(connection, Default::default()).await
+    }
+
+    /// Points the player to a new voice server, like the ones Discord sends when a guild changes region
+    ///
+    /// Keeps the session and channel of the last connection, so playback continues without reconnecting
+    pub async fn update_voice_server(
+        &self,
+        token: impl Into<String>,
+        endpoint: impl Into<String>,
+    ) -> Result<(), LavalinkPlayerError> {
+        let node = self.node();
+
+        let Some(last) = node
+            .voices
+            .read_async(&self.guild_id, |_, voice| voice.clone())
+            .await
+        else {
+            return Err(LavalinkPlayerError::NoVoiceConnection);
+        };
+
+        let voice = LavalinkVoice {
+            token: token.into(),
+            endpoint: endpoint.into(),
+            ..last
+        };
+
+        let options = LavalinkPlayerOptions {
+            voice: Some(voice.clone()),
+            ..Default::default()
+        };
+
+        self.send_update_player(false, options).await?;
+
+        node.voices.upsert_async(self.guild_id, voice).await;
+
+        Ok(())
+    }
+
+    /// Sends the connection info of the player along with other options in a single update
+    async fn connect(
         &self,
         connection: ConnectionOptions,
+        mut options: LavalinkPlayerOptions,
     ) -> Result<(), LavalinkPlayerError> {
+        self.location
+            .set_voice_state(connection.self_deaf, connection.self_mute);
+
         let voice = LavalinkVoice {
             token: connection.token,
             endpoint: connection.endpoint,
@@ -157,22 +870,134 @@ impl Player {
             ping: None,
         };
 
-        let mut options: LavalinkPlayerOptions = Default::default();
-
-        let _ = options.voice.insert(voice);
+        let _ = options.voice.insert(voice.clone());
 
         self.send_update_player(false, options).await?;
 
+        self.node().voices.upsert_async(self.guild_id, voice).await;
+
+        Ok(())
+    }
+
+    /// Checks if the node of this player has every filter that is set enabled, using its cached info
+    async fn check_filters(&self, filters: &LavalinkFilters) -> Result<(), LavalinkPlayerError> {
+        let node = self.node();
+        let capabilities = node.capabilities().await?;
+
+        match filters
+            .names()
+            .into_iter()
+            .find(|name| !capabilities.supports_filter(name))
+        {
+            Some(filter) => Err(LavalinkPlayerError::FilterDisabledOnNode {
+                filter,
+                node: node.name,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks if a volume is within the range a player accepts
+    fn check_volume(volume: u32, max: u32) -> Result<(), LavalinkPlayerError> {
+        if volume > max {
+            return Err(LavalinkPlayerError::InvalidVolume { volume, max });
+        }
+
         Ok(())
     }
 
+    /// Cancels the running fade, then registers a new one
+    fn begin_fade(&self) -> CancellationToken {
+        let token = CancellationToken::new();
+
+        if let Some(previous) = self.fade.lock().unwrap().replace(token.clone()) {
+            previous.cancel();
+        }
+
+        token
+    }
+
+    /// Runs a fade in the background, starting from the current filter volume if `from` is none
+    fn spawn_fade(&self, from: Option<f64>, to: f64, duration: Duration) -> Fade {
+        let token = self.begin_fade();
+        let player = self.clone();
+        let task_token = token.clone();
+
+        let handle = tokio::spawn(async move {
+            let filters = player.get_data().await?.filters;
+            let from = from.unwrap_or(filters.volume.unwrap_or(1.0));
+
+            player
+                .ramp_volume(&filters, from, to, duration, &task_token)
+                .await
+        });
+
+        Fade { token, handle }
+    }
+
+    /// Changes the filter volume step by step, keeping the other filters as they are
+    ///
+    /// Returns false if the token was cancelled before the ramp finished
+    async fn ramp_volume(
+        &self,
+        filters: &LavalinkFilters,
+        from: f64,
+        to: f64,
+        duration: Duration,
+        token: &CancellationToken,
+    ) -> Result<bool, LavalinkPlayerError> {
+        let steps = (duration.as_millis() / FADE_STEP.as_millis()).max(1) as u32;
+        let interval = duration / steps;
+
+        for step in 1..=steps {
+            if token.is_cancelled() {
+                return Ok(false);
+            }
+
+            let mut filters = filters.clone();
+            let _ = filters
+                .volume
+                .insert(from + (to - from) * (step as f64 / steps as f64));
+
+            let options = LavalinkPlayerOptions {
+                filters: Some(filters),
+                ..Default::default()
+            };
+
+            self.send_update_player(false, options).await?;
+
+            if step < steps {
+                tokio::select! {
+                    _ = sleep(interval) => {}
+                    _ = token.cancelled() => return Ok(false),
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Sends an event to the subscriber of this player, if it's still registered
+    async fn send_event(&self, event: EventType) {
+        let Some(sender) = self
+            .node()
+            .events_sender
+            .read_async(&self.guild_id, |_, sender| sender.clone())
+            .await
+        else {
+            return;
+        };
+
+        sender.send(event).await;
+    }
+
     /// Sends the updated player data to lavalink
     async fn send_update_player(
         &self,
         no_replace: bool,
         options: LavalinkPlayerOptions,
     ) -> Result<(), LavalinkPlayerError> {
-        self.node
+        self.node()
             .rest
             .update_player(self.guild_id, no_replace, options)
             .await?;
@@ -180,3 +1005,8 @@ impl Player {
         Ok(())
     }
 }
+
+/// Converts a duration to the milliseconds lavalink expects, saturating on overflow
+fn duration_to_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
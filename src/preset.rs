@@ -0,0 +1,130 @@
+use futures::future::BoxFuture;
+use scc::HashMap as ConcurrentHashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use std::result::Result;
+use std::sync::Arc;
+
+use crate::model::error::PresetError;
+use crate::model::player::LavalinkFilters;
+
+/// Named bundle of filters and volume, applied to a player at once
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Preset {
+    /// Filters the player is set to, replacing the ones it has
+    pub filters: Option<LavalinkFilters>,
+    /// Volume the player is set to
+    pub volume: Option<u32>,
+}
+
+/// Saves registered presets, so they survive restarts
+///
+/// Implement this to back the presets with your own storage (e.g. Redis)
+pub trait PresetStore: Send + Sync {
+    /// Loads every saved preset, mapped by name
+    fn load(&self) -> BoxFuture<'_, Result<Vec<(String, Preset)>, PresetError>>;
+    /// Saves a preset
+    fn save<'a>(
+        &'a self,
+        name: &'a str,
+        preset: &'a Preset,
+    ) -> BoxFuture<'a, Result<(), PresetError>>;
+    /// Removes a saved preset
+    fn remove<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), PresetError>>;
+}
+
+/// Presets players can apply by name, optionally kept in sync with a [`PresetStore`]
+#[derive(Clone, Default)]
+pub struct Presets {
+    entries: Arc<ConcurrentHashMap<String, Preset>>,
+    store: Option<Arc<dyn PresetStore>>,
+}
+
+impl Debug for Presets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Presets")
+            .field("entries", &self.entries.len())
+            .field("store", &self.store.is_some())
+            .finish()
+    }
+}
+
+impl Presets {
+    /// Creates an empty set of presets, backed by a store if there is one
+    pub fn new(store: Option<Arc<dyn PresetStore>>) -> Self {
+        Self {
+            entries: Arc::new(ConcurrentHashMap::new()),
+            store,
+        }
+    }
+
+    /// Loads the presets saved on the store, replacing registered ones with the same name
+    /// # Returns the amount of presets loaded
+    pub async fn load(&self) -> Result<usize, PresetError> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        let presets = store.load().await?;
+        let amount = presets.len();
+
+        for (name, preset) in presets {
+            self.entries.upsert_async(name, preset).await;
+        }
+
+        Ok(amount)
+    }
+
+    /// Registers a preset, replacing the one with the same name if there is one
+    pub async fn register(
+        &self,
+        name: impl Into<String>,
+        preset: Preset,
+    ) -> Result<(), PresetError> {
+        let name = name.into();
+
+        if let Some(store) = &self.store {
+            store.save(&name, &preset).await?;
+        }
+
+        self.entries.upsert_async(name, preset).await;
+
+        Ok(())
+    }
+
+    /// Gets a preset by name
+    pub async fn get(&self, name: &str) -> Option<Preset> {
+        self.entries
+            .read_async(name, |_, preset| preset.clone())
+            .await
+    }
+
+    /// Names of every registered preset, sorted
+    pub async fn list(&self) -> Vec<String> {
+        let mut names = vec![];
+
+        self.entries
+            .iter_async(|name, _| {
+                names.push(name.clone());
+                true
+            })
+            .await;
+
+        names.sort();
+
+        names
+    }
+
+    /// Removes a preset, returning it if it was registered
+    pub async fn remove(&self, name: &str) -> Result<Option<Preset>, PresetError> {
+        if let Some(store) = &self.store {
+            store.remove(name).await?;
+        }
+
+        Ok(self
+            .entries
+            .remove_async(name)
+            .await
+            .map(|(_, preset)| preset))
+    }
+}
@@ -0,0 +1,13 @@
+use futures::future::BoxFuture;
+
+use crate::model::id::GuildId;
+use crate::model::player::Track;
+
+/// Picks a track to play once a queue with autoplay enabled runs out
+///
+/// Implement this to plug in a recommendation source (e.g. YouTube mixes, LavaSrc recommendations)
+pub trait Autoplay: Send + Sync {
+    /// Gets the track to play after the last track of a guild, none stops the playback
+    fn next<'a>(&'a self, guild_id: GuildId, last_track: &'a Track)
+    -> BoxFuture<'a, Option<Track>>;
+}
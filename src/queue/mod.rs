@@ -0,0 +1,872 @@
+use scc::HashMap as ConcurrentHashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use std::result::Result;
+use std::sync::Arc;
+
+use crate::model::dedup::DedupStrategy;
+use crate::model::error::QueueError;
+use crate::model::id::GuildId;
+use crate::model::player::{Track, TrackEndReason};
+
+/// Hook that picks tracks once a queue runs out
+pub mod autoplay;
+/// Persistence backends for queues
+pub mod store;
+
+pub use autoplay::Autoplay;
+pub use store::{MemoryQueueStore, QueueStore};
+
+/// Amount of played tracks a queue keeps by default
+pub const HISTORY_SIZE: usize = 50;
+
+/// Tracks queued for a player
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Queue {
+    /// Track that the queue last started
+    pub current: Option<Track>,
+    /// Tracks waiting to be played
+    pub tracks: VecDeque<Track>,
+    /// If [`Autoplay`] picks the next track once this queue runs out
+    #[serde(default)]
+    pub autoplay: bool,
+    /// Policies this queue follows when tracks are added
+    #[serde(default)]
+    pub options: QueueOptions,
+    /// Times the current track failed to load in a row
+    #[serde(default)]
+    pub failures: u32,
+    /// Tracks that played before the current one, oldest first
+    #[serde(default)]
+    pub history: VecDeque<Track>,
+    /// What plays once the current track finishes
+    #[serde(default)]
+    pub loop_mode: LoopMode,
+    /// Position of the current track in milliseconds, as of when it was last saved
+    #[serde(default)]
+    pub position: u64,
+}
+
+/// What a queue plays once its current track finishes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LoopMode {
+    /// Next queued track plays
+    #[default]
+    Off,
+    /// Current track plays again
+    Track,
+    /// Current track goes back to the end of the queue, then the next one plays
+    Queue,
+}
+
+/// Policies a queue follows when tracks are added and when they end
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueOptions {
+    /// How tracks that are already queued are treated
+    pub duplicates: DuplicatePolicy,
+    /// Interleaves the tracks of each requester round-robin, instead of playing them in order
+    pub fair_play: bool,
+    /// Times a track that failed to load is played again before it's skipped
+    #[serde(default)]
+    pub load_failed_retries: u32,
+    /// Amount of played tracks kept for going back to them, none are kept if 0
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    /// Keeps the tracks skipped by jumping ahead in the history, instead of dropping them
+    #[serde(default)]
+    pub keep_skipped: bool,
+    /// Most tracks that can wait to be played, unlimited if none
+    #[serde(default)]
+    pub max_len: Option<usize>,
+    /// What happens when a track is added to a queue that reached its max length
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for QueueOptions {
+    fn default() -> Self {
+        Self {
+            duplicates: DuplicatePolicy::default(),
+            fair_play: false,
+            load_failed_retries: 0,
+            history_size: HISTORY_SIZE,
+            keep_skipped: false,
+            max_len: None,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+fn default_history_size() -> usize {
+    HISTORY_SIZE
+}
+
+/// How a queue treats a track that is already queued
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    /// Duplicate tracks are queued
+    #[default]
+    Allow,
+    /// Tracks with the same encoded data as a queued track are rejected
+    RejectEncoded,
+    /// Tracks with the same ISRC as a queued track are rejected, falling back to the source and identifier
+    RejectIsrc,
+}
+
+/// What a queue does when a track is added after it reached its max length
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    /// Track is rejected
+    #[default]
+    Reject,
+    /// Track that would play next is dropped to make room
+    DropOldest,
+    /// Track that was queued last is dropped to make room
+    DropNewest,
+}
+
+impl DuplicatePolicy {
+    /// If both tracks count as the same track under this policy
+    fn matches(self, a: &Track, b: &Track) -> bool {
+        match self {
+            DuplicatePolicy::Allow => false,
+            DuplicatePolicy::RejectEncoded => a.same_as(b, DedupStrategy::Encoded),
+            DuplicatePolicy::RejectIsrc => a.same_as(b, DedupStrategy::Isrc),
+        }
+    }
+}
+
+impl Queue {
+    /// Serializes this queue into json, with the encoded tracks, loop mode, history and position
+    pub fn to_json(&self) -> Result<String, QueueError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserializes a queue from the json [`Queue::to_json`] makes
+    pub fn from_json(data: &str) -> Result<Self, QueueError> {
+        Ok(serde_json::from_str(data)?)
+    }
+
+    /// Adds a track to the queue, at the end or at its requester's turn if fair play is enabled
+    pub fn push(&mut self, track: Track) -> Result<(), QueueError> {
+        self.check_duplicate(&track)?;
+        self.make_room()?;
+
+        if !self.options.fair_play {
+            self.tracks.push_back(track);
+            return Ok(());
+        }
+
+        let index = self.fair_index(track.requester());
+
+        self.tracks.insert(index, track);
+
+        Ok(())
+    }
+
+    /// Adds a track at a position in the queue, 0 being the next one to play
+    pub fn insert_at(&mut self, index: usize, track: Track) -> Result<(), QueueError> {
+        if index > self.tracks.len() {
+            return Err(self.out_of_bounds(index));
+        }
+
+        self.check_duplicate(&track)?;
+        self.make_room()?;
+
+        let index = index.min(self.tracks.len());
+
+        self.tracks.insert(index, track);
+
+        Ok(())
+    }
+
+    /// Moves a queued track to another position, shifting the tracks between them
+    pub fn move_track(&mut self, from: usize, to: usize) -> Result<(), QueueError> {
+        self.check_index(from)?;
+        self.check_index(to)?;
+
+        if let Some(track) = self.tracks.remove(from) {
+            self.tracks.insert(to, track);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps the positions of two queued tracks
+    pub fn swap(&mut self, a: usize, b: usize) -> Result<(), QueueError> {
+        self.check_index(a)?;
+        self.check_index(b)?;
+
+        self.tracks.swap(a, b);
+
+        Ok(())
+    }
+
+    /// Removes a range of queued tracks, and returns them
+    pub fn remove_range(&mut self, range: Range<usize>) -> Result<Vec<Track>, QueueError> {
+        if range.end > self.tracks.len() {
+            return Err(self.out_of_bounds(range.end));
+        }
+
+        if range.start > range.end {
+            return Err(self.out_of_bounds(range.start));
+        }
+
+        Ok(self.tracks.drain(range).collect())
+    }
+
+    /// Skips ahead to a queued track, making it the current one, and returns it
+    ///
+    /// The current track goes to the history first, then the tracks before it in queue order if [`QueueOptions::keep_skipped`] is enabled.
+    /// Otherwise the tracks before it are dropped
+    pub fn jump_to(&mut self, index: usize) -> Result<Track, QueueError> {
+        self.check_index(index)?;
+
+        let skipped: Vec<Track> = self.tracks.drain(..index).collect();
+
+        if let Some(current) = self.current.take() {
+            self.remember(current);
+        }
+
+        if self.options.keep_skipped {
+            for track in skipped {
+                self.remember(track);
+            }
+        }
+
+        self.advance().ok_or(QueueError::Empty)
+    }
+
+    /// Puts the current track back at the end of the queue, the way [`LoopMode::Queue`] loops it
+    /// # Leaves the loop if a duplicate is queued or the queue is full, looping never evicts a queued track
+    fn requeue_current(&mut self) -> Result<(), QueueError> {
+        let Some(current) = self.current.clone() else {
+            return Ok(());
+        };
+
+        let duplicates = self.options.duplicates;
+
+        // the current track is leaving, only the waiting tracks can be duplicates of it
+        if self
+            .tracks
+            .iter()
+            .any(|queued| duplicates.matches(queued, &current))
+        {
+            return Err(QueueError::DuplicateTrack);
+        }
+
+        if let Some(max) = self.options.max_len
+            && self.tracks.len() >= max
+        {
+            return Err(QueueError::Full(max));
+        }
+
+        self.tracks.push_back(current);
+
+        Ok(())
+    }
+
+    /// Errors if a track with the same identity is already queued, as the duplicate policy defines it
+    fn check_duplicate(&self, track: &Track) -> Result<(), QueueError> {
+        let duplicates = self.options.duplicates;
+
+        if self
+            .current
+            .iter()
+            .chain(self.tracks.iter())
+            .any(|queued| duplicates.matches(queued, track))
+        {
+            return Err(QueueError::DuplicateTrack);
+        }
+
+        Ok(())
+    }
+
+    /// Drops a track if the queue is full and the overflow policy allows it, errors if it doesn't
+    fn make_room(&mut self) -> Result<(), QueueError> {
+        let Some(max) = self.options.max_len else {
+            return Ok(());
+        };
+
+        if self.tracks.len() < max {
+            return Ok(());
+        }
+
+        if max == 0 || self.options.overflow == OverflowPolicy::Reject {
+            return Err(QueueError::Full(max));
+        }
+
+        let excess = self.tracks.len() + 1 - max;
+
+        match self.options.overflow {
+            OverflowPolicy::DropOldest => drop(self.tracks.drain(..excess)),
+            OverflowPolicy::DropNewest => self.tracks.truncate(max - 1),
+            OverflowPolicy::Reject => {}
+        }
+
+        Ok(())
+    }
+
+    /// Errors if there is no queued track at an index
+    fn check_index(&self, index: usize) -> Result<(), QueueError> {
+        if index >= self.tracks.len() {
+            return Err(self.out_of_bounds(index));
+        }
+
+        Ok(())
+    }
+
+    fn out_of_bounds(&self, index: usize) -> QueueError {
+        QueueError::OutOfBounds {
+            index,
+            len: self.tracks.len(),
+        }
+    }
+
+    /// Finds where a track goes so every requester gets a turn before anyone gets a second one
+    fn fair_index(&self, requester: Option<String>) -> usize {
+        let turn = self
+            .tracks
+            .iter()
+            .filter(|track| track.requester() == requester)
+            .count();
+
+        let mut turns: HashMap<Option<String>, usize> = HashMap::new();
+
+        for (index, track) in self.tracks.iter().enumerate() {
+            let count = turns.entry(track.requester()).or_default();
+
+            if *count > turn {
+                return index;
+            }
+
+            *count += 1;
+        }
+
+        self.tracks.len()
+    }
+
+    /// Moves the next track into current, and returns it. The previous current track goes to the history
+    pub fn advance(&mut self) -> Option<Track> {
+        self.failures = 0;
+        self.position = 0;
+
+        let next = self.tracks.pop_front();
+
+        if let Some(last) = std::mem::replace(&mut self.current, next) {
+            self.remember(last);
+        }
+
+        self.current.clone()
+    }
+
+    /// Moves the last played track back into current, and returns it. The current track goes back to the front
+    pub fn previous(&mut self) -> Option<Track> {
+        let track = self.history.pop_back()?;
+
+        self.failures = 0;
+        self.position = 0;
+
+        if let Some(current) = self.current.replace(track) {
+            self.tracks.push_front(current);
+        }
+
+        self.current.clone()
+    }
+
+    /// Adds a played track to the history, forgetting the oldest ones past the history size
+    fn remember(&mut self, track: Track) {
+        if self.options.history_size == 0 {
+            return;
+        }
+
+        self.history.push_back(track);
+
+        while self.history.len() > self.options.history_size {
+            self.history.pop_front();
+        }
+    }
+
+    /// Removes every queued track, and the current one
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.tracks.clear();
+    }
+
+    /// Amount of tracks waiting to be played
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// If there are no tracks waiting to be played
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+}
+
+/// Queues of every player, mapped by Guild Id and kept in sync with a [`QueueStore`]
+#[derive(Clone)]
+pub struct Queues {
+    entries: Arc<ConcurrentHashMap<GuildId, Queue>>,
+    store: Arc<dyn QueueStore>,
+    autoplay: Option<Arc<dyn Autoplay>>,
+}
+
+impl Debug for Queues {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Queues")
+            .field("entries", &self.entries.len())
+            .field("autoplay", &self.autoplay.is_some())
+            .finish()
+    }
+}
+
+impl Queues {
+    /// Creates an empty set of queues backed by a store
+    pub fn new(store: Arc<dyn QueueStore>, autoplay: Option<Arc<dyn Autoplay>>) -> Self {
+        Self {
+            entries: Arc::new(ConcurrentHashMap::new()),
+            store,
+            autoplay,
+        }
+    }
+
+    /// Gets the queue of a guild, loading it from the store if it's not cached yet
+    pub async fn get(&self, guild_id: GuildId) -> Result<Queue, QueueError> {
+        self.load(guild_id).await?;
+
+        Ok(self
+            .entries
+            .read_async(&guild_id, |_, queue| queue.clone())
+            .await
+            .unwrap_or_default())
+    }
+
+    /// Gets the current track of a guild from the cached queues only, without going to the store
+    pub async fn cached_current(&self, guild_id: GuildId) -> Option<Track> {
+        self.entries
+            .read_async(&guild_id, |_, queue| queue.current.clone())
+            .await
+            .flatten()
+    }
+
+    /// Modifies the queue of a guild, then saves it to the store
+    pub async fn update<R>(
+        &self,
+        guild_id: GuildId,
+        update: impl FnOnce(&mut Queue) -> R,
+    ) -> Result<R, QueueError> {
+        self.load(guild_id).await?;
+
+        let (result, queue) = {
+            let mut entry = self.entries.entry_async(guild_id).await.or_default();
+            let result = update(entry.get_mut());
+
+            (result, entry.get().clone())
+        };
+
+        self.store.save(guild_id, &queue).await?;
+
+        Ok(result)
+    }
+
+    /// Advances the queue of a guild, asking [`Autoplay`] for a track if it ran out
+    pub async fn next_track(&self, guild_id: GuildId) -> Result<Option<Track>, QueueError> {
+        let (last, next, autoplay) = self
+            .update(guild_id, |queue| {
+                let last = queue.current.clone();
+                (last, queue.advance(), queue.autoplay)
+            })
+            .await?;
+
+        if next.is_some() || !autoplay {
+            return Ok(next);
+        }
+
+        let (Some(provider), Some(last)) = (&self.autoplay, last) else {
+            return Ok(None);
+        };
+
+        let Some(track) = provider.next(guild_id, &last).await else {
+            return Ok(None);
+        };
+
+        self.update(guild_id, |queue| queue.current = Some(track.clone()))
+            .await?;
+
+        Ok(Some(track))
+    }
+
+    /// Picks what plays after a track ended, based on why it ended
+    ///
+    /// Finished tracks play again or advance the queue, as its loop mode says. Tracks that failed to load are retried as configured, then skipped.
+    /// Tracks that were stopped, replaced, cleaned up or ended for an unknown reason leave the queue as it is
+    pub async fn track_ended(
+        &self,
+        guild_id: GuildId,
+        reason: TrackEndReason,
+    ) -> Result<Option<Track>, QueueError> {
+        match reason {
+            TrackEndReason::Finished => {
+                let repeat = self
+                    .update(guild_id, |queue| match queue.loop_mode {
+                        LoopMode::Off => None,
+                        LoopMode::Track => {
+                            queue.position = 0;
+                            queue.current.clone()
+                        }
+                        LoopMode::Queue => {
+                            if let Err(error) = queue.requeue_current() {
+                                tracing::debug!(
+                                    "Finished track of guild {} left the loop => {:?}",
+                                    guild_id,
+                                    error
+                                );
+                            }
+
+                            None
+                        }
+                    })
+                    .await?;
+
+                match repeat {
+                    Some(track) => Ok(Some(track)),
+                    None => self.next_track(guild_id).await,
+                }
+            }
+            TrackEndReason::LoadFailed => {
+                let retry = self
+                    .update(guild_id, |queue| {
+                        if queue.failures >= queue.options.load_failed_retries {
+                            return None;
+                        }
+
+                        queue.failures += 1;
+                        queue.current.clone()
+                    })
+                    .await?;
+
+                match retry {
+                    Some(track) => Ok(Some(track)),
+                    None => self.next_track(guild_id).await,
+                }
+            }
+            TrackEndReason::Stopped
+            | TrackEndReason::Replaced
+            | TrackEndReason::Cleanup
+            | TrackEndReason::Unknown => Ok(None),
+        }
+    }
+
+    /// Removes the queue of a guild from the cache and the store
+    pub async fn remove(&self, guild_id: GuildId) -> Result<(), QueueError> {
+        self.entries.remove_async(&guild_id).await;
+        self.store.remove(guild_id).await
+    }
+
+    /// Caches the stored queue of a guild if it's not cached yet
+    async fn load(&self, guild_id: GuildId) -> Result<(), QueueError> {
+        if self
+            .entries
+            .read_async(&guild_id, |_, _| ())
+            .await
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let queue = self.store.load(guild_id).await?.unwrap_or_default();
+
+        // another task could have loaded it first, keep theirs
+        let _ = self.entries.insert_async(guild_id, queue).await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::model::player::TrackInfo;
+
+    fn track(identifier: &str) -> Track {
+        Track {
+            encoded: format!("encoded-{identifier}"),
+            info: TrackInfo::new(identifier, identifier, "Author", 1000, "http"),
+            plugin_info: Value::Null,
+            user_data: None,
+        }
+    }
+
+    fn requested(identifier: &str, requester: &str) -> Track {
+        Track {
+            user_data: Some(json!({ "requester": requester })),
+            ..track(identifier)
+        }
+    }
+
+    fn identifiers<'a>(tracks: impl IntoIterator<Item = &'a Track>) -> Vec<&'a str> {
+        tracks
+            .into_iter()
+            .map(|track| track.info.identifier.as_str())
+            .collect()
+    }
+
+    fn queue_of(names: &[&str]) -> Queue {
+        let mut queue = Queue::default();
+
+        for name in names {
+            queue.push(track(name)).unwrap();
+        }
+
+        queue
+    }
+
+    #[test]
+    fn fair_play_gives_every_requester_a_turn() {
+        let mut queue = Queue::default();
+        queue.options.fair_play = true;
+
+        for track in [
+            requested("a1", "a"),
+            requested("a2", "a"),
+            requested("a3", "a"),
+            requested("b1", "b"),
+            requested("c1", "c"),
+            requested("b2", "b"),
+        ] {
+            queue.push(track).unwrap();
+        }
+
+        assert_eq!(
+            identifiers(&queue.tracks),
+            ["a1", "b1", "c1", "a2", "b2", "a3"]
+        );
+    }
+
+    #[test]
+    fn advance_and_previous_walk_the_history() {
+        let mut queue = queue_of(&["1", "2", "3"]);
+
+        assert_eq!(queue.advance().unwrap().info.identifier, "1");
+        assert_eq!(queue.advance().unwrap().info.identifier, "2");
+        assert_eq!(identifiers(&queue.history), ["1"]);
+
+        assert_eq!(queue.previous().unwrap().info.identifier, "1");
+        assert_eq!(identifiers(&queue.tracks), ["2", "3"]);
+        assert!(queue.history.is_empty());
+        assert!(queue.previous().is_none());
+    }
+
+    #[test]
+    fn jump_to_drops_skipped_tracks() {
+        let mut queue = queue_of(&["1", "2", "3", "4"]);
+        queue.advance();
+
+        assert_eq!(queue.jump_to(1).unwrap().info.identifier, "3");
+        assert_eq!(identifiers(&queue.tracks), ["4"]);
+        assert_eq!(identifiers(&queue.history), ["1"]);
+        assert!(matches!(
+            queue.jump_to(1),
+            Err(QueueError::OutOfBounds { index: 1, len: 1 })
+        ));
+    }
+
+    #[test]
+    fn jump_to_keeps_current_then_skipped_tracks() {
+        let mut queue = queue_of(&["1", "2", "3", "4", "5"]);
+        queue.options.keep_skipped = true;
+        queue.advance();
+
+        assert_eq!(queue.jump_to(2).unwrap().info.identifier, "4");
+        assert_eq!(identifiers(&queue.history), ["1", "2", "3"]);
+        assert_eq!(queue.previous().unwrap().info.identifier, "3");
+        assert_eq!(identifiers(&queue.tracks), ["4", "5"]);
+    }
+
+    #[test]
+    fn overflow_policies_make_room_or_reject() {
+        let mut queue = queue_of(&["1", "2"]);
+        queue.options.max_len = Some(2);
+
+        assert!(matches!(queue.push(track("3")), Err(QueueError::Full(2))));
+
+        queue.options.overflow = OverflowPolicy::DropOldest;
+        queue.push(track("3")).unwrap();
+        assert_eq!(identifiers(&queue.tracks), ["2", "3"]);
+
+        queue.options.overflow = OverflowPolicy::DropNewest;
+        queue.push(track("4")).unwrap();
+        assert_eq!(identifiers(&queue.tracks), ["2", "4"]);
+    }
+
+    #[test]
+    fn duplicate_policies_reject_queued_tracks() {
+        let mut queue = queue_of(&["1"]);
+
+        queue.push(track("1")).unwrap();
+
+        queue.options.duplicates = DuplicatePolicy::RejectEncoded;
+        assert!(matches!(
+            queue.push(track("1")),
+            Err(QueueError::DuplicateTrack)
+        ));
+        queue.push(track("2")).unwrap();
+
+        let mut isrc = track("3");
+        isrc.info.isrc = Some(String::from("USRC17607839"));
+        queue.push(isrc.clone()).unwrap();
+
+        let mut other_source = track("4");
+        other_source.info.isrc = isrc.info.isrc.clone();
+
+        queue.options.duplicates = DuplicatePolicy::RejectIsrc;
+        assert!(matches!(
+            queue.push(other_source),
+            Err(QueueError::DuplicateTrack)
+        ));
+    }
+
+    #[test]
+    fn json_round_trip_keeps_the_queue() {
+        let mut queue = queue_of(&["1", "2", "3"]);
+        queue.advance();
+        queue.advance();
+        queue.loop_mode = LoopMode::Queue;
+        queue.position = 1500;
+        queue.options.fair_play = true;
+
+        let json = queue.to_json().unwrap();
+
+        assert_eq!(Queue::from_json(&json).unwrap(), queue);
+    }
+
+    #[tokio::test]
+    async fn loop_modes_pick_what_plays_next() {
+        let queues = Queues::new(Arc::new(MemoryQueueStore::default()), None);
+        let guild_id = GuildId::new(1);
+
+        queues
+            .update(guild_id, |queue| {
+                *queue = queue_of(&["1", "2"]);
+                queue.advance();
+                queue.loop_mode = LoopMode::Track;
+            })
+            .await
+            .unwrap();
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().info.identifier, "1");
+
+        queues
+            .update(guild_id, |queue| queue.loop_mode = LoopMode::Queue)
+            .await
+            .unwrap();
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().info.identifier, "2");
+        assert_eq!(
+            identifiers(&queues.get(guild_id).await.unwrap().tracks),
+            ["1"]
+        );
+
+        queues
+            .update(guild_id, |queue| queue.loop_mode = LoopMode::Off)
+            .await
+            .unwrap();
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().info.identifier, "1");
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert!(next.is_none());
+
+        let stopped = queues
+            .track_ended(guild_id, TrackEndReason::Stopped)
+            .await
+            .unwrap();
+        assert!(stopped.is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_end_reasons_leave_the_queue() {
+        let queues = Queues::new(Arc::new(MemoryQueueStore::default()), None);
+        let guild_id = GuildId::new(1);
+
+        queues
+            .update(guild_id, |queue| {
+                *queue = queue_of(&["1", "2"]);
+                queue.advance();
+            })
+            .await
+            .unwrap();
+
+        let reason: TrackEndReason = serde_json::from_value(json!("somethingNew")).unwrap();
+        assert_eq!(reason, TrackEndReason::Unknown);
+
+        let next = queues.track_ended(guild_id, reason).await.unwrap();
+        assert!(next.is_none());
+        assert_eq!(
+            identifiers(&queues.get(guild_id).await.unwrap().tracks),
+            ["2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn queue_loop_respects_max_length() {
+        let queues = Queues::new(Arc::new(MemoryQueueStore::default()), None);
+        let guild_id = GuildId::new(1);
+
+        queues
+            .update(guild_id, |queue| {
+                *queue = queue_of(&["1", "2"]);
+                queue.advance();
+                queue.loop_mode = LoopMode::Queue;
+                queue.options.max_len = Some(1);
+            })
+            .await
+            .unwrap();
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().info.identifier, "2");
+        assert!(queues.get(guild_id).await.unwrap().tracks.is_empty());
+
+        queues
+            .update(guild_id, |queue| {
+                queue.options.overflow = OverflowPolicy::DropOldest
+            })
+            .await
+            .unwrap();
+        queues
+            .update(guild_id, |queue| queue.push(track("3")))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let next = queues
+            .track_ended(guild_id, TrackEndReason::Finished)
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().info.identifier, "3");
+        assert!(queues.get(guild_id).await.unwrap().tracks.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+use futures::future::BoxFuture;
+use scc::HashMap as ConcurrentHashMap;
+use std::result::Result;
+
+use crate::model::error::QueueError;
+use crate::model::id::GuildId;
+use crate::queue::Queue;
+
+/// Saves and loads queues per guild, so they can survive restarts
+///
+/// Implement this to back the queues with your own storage
+pub trait QueueStore: Send + Sync {
+    /// Loads the saved queue of a guild
+    fn load(&self, guild_id: GuildId) -> BoxFuture<'_, Result<Option<Queue>, QueueError>>;
+    /// Saves the queue of a guild
+    fn save<'a>(
+        &'a self,
+        guild_id: GuildId,
+        queue: &'a Queue,
+    ) -> BoxFuture<'a, Result<(), QueueError>>;
+    /// Removes the saved queue of a guild
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, Result<(), QueueError>>;
+}
+
+/// In-memory queue store, used by default. Queues don't survive restarts with this
+#[derive(Default, Debug)]
+pub struct MemoryQueueStore {
+    queues: ConcurrentHashMap<GuildId, Queue>,
+}
+
+impl QueueStore for MemoryQueueStore {
+    fn load(&self, guild_id: GuildId) -> BoxFuture<'_, Result<Option<Queue>, QueueError>> {
+        Box::pin(async move {
+            Ok(self
+                .queues
+                .read_async(&guild_id, |_, queue| queue.clone())
+                .await)
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        guild_id: GuildId,
+        queue: &'a Queue,
+    ) -> BoxFuture<'a, Result<(), QueueError>> {
+        Box::pin(async move {
+            self.queues.upsert_async(guild_id, queue.clone()).await;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, Result<(), QueueError>> {
+        Box::pin(async move {
+            self.queues.remove_async(&guild_id).await;
+            Ok(())
+        })
+    }
+}
+
+/// Queue store backed by Redis, saving each queue as json
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisQueueStore {
+    connection: redis::aio::MultiplexedConnection,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+impl RedisQueueStore {
+    /// Creates a new store, keys are saved as `{prefix}:{guild_id}`
+    pub async fn new(client: &redis::Client, prefix: &str) -> Result<Self, QueueError> {
+        Ok(Self {
+            connection: client.get_multiplexed_async_connection().await?,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn key(&self, guild_id: GuildId) -> String {
+        format!("{}:{}", self.prefix, guild_id)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl QueueStore for RedisQueueStore {
+    fn load(&self, guild_id: GuildId) -> BoxFuture<'_, Result<Option<Queue>, QueueError>> {
+        use redis::AsyncCommands;
+
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+            let data: Option<String> = connection.get(self.key(guild_id)).await?;
+
+            Ok(data.map(|data| Queue::from_json(&data)).transpose()?)
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        guild_id: GuildId,
+        queue: &'a Queue,
+    ) -> BoxFuture<'a, Result<(), QueueError>> {
+        use redis::AsyncCommands;
+
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+            let data = queue.to_json()?;
+            let _: () = connection.set(self.key(guild_id), data).await?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, Result<(), QueueError>> {
+        use redis::AsyncCommands;
+
+        Box::pin(async move {
+            let mut connection = self.connection.clone();
+            let _: () = connection.del(self.key(guild_id)).await?;
+            Ok(())
+        })
+    }
+}
+
+/// Queue store backed by a sled tree, saving each queue as json
+#[cfg(feature = "sled")]
+#[derive(Clone, Debug)]
+pub struct SledQueueStore {
+    tree: sled::Tree,
+}
+
+#[cfg(feature = "sled")]
+impl SledQueueStore {
+    /// Creates a new store that saves the queues on a tree
+    pub fn new(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+}
+
+#[cfg(feature = "sled")]
+impl QueueStore for SledQueueStore {
+    fn load(&self, guild_id: GuildId) -> BoxFuture<'_, Result<Option<Queue>, QueueError>> {
+        Box::pin(async move {
+            let Some(data) = self.tree.get(guild_id.get().to_be_bytes())? else {
+                return Ok(None);
+            };
+
+            Ok(Some(serde_json::from_slice::<Queue>(&data)?))
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        guild_id: GuildId,
+        queue: &'a Queue,
+    ) -> BoxFuture<'a, Result<(), QueueError>> {
+        Box::pin(async move {
+            let data = serde_json::to_vec(queue)?;
+            self.tree.insert(guild_id.get().to_be_bytes(), data)?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, guild_id: GuildId) -> BoxFuture<'_, Result<(), QueueError>> {
+        Box::pin(async move {
+            self.tree.remove(guild_id.get().to_be_bytes())?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use crate::model::id::GuildId;
+
+/// Picks the gateway shard a guild belongs to, so its voice state updates are sent on the right one
+///
+/// Implement this when the bot routes guilds to shards in a custom way
+pub trait ShardRouter: Send + Sync {
+    /// Gets the shard of a guild
+    fn shard(&self, guild_id: GuildId) -> u64;
+}
+
+/// Routes guilds the way Discord does with a fixed amount of shards, used by default with one shard
+#[derive(Clone, Copy, Debug)]
+pub struct ShardCount(pub u64);
+
+impl Default for ShardCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl ShardRouter for ShardCount {
+    fn shard(&self, guild_id: GuildId) -> u64 {
+        shard_for_guild(guild_id, self.0)
+    }
+}
+
+/// Gets the shard of a guild with Discord's formula, `(guild_id >> 22) % shard_count`
+pub fn shard_for_guild(guild_id: impl Into<GuildId>, shard_count: u64) -> u64 {
+    (guild_id.into().get() >> 22) % shard_count.max(1)
+}
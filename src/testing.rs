@@ -0,0 +1,85 @@
+use std::result::Result;
+use testcontainers::core::{IntoContainerPort, WaitFor};
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+use crate::model::anchorage::NodeOptions;
+use crate::model::error::AnchorageError;
+
+/// Port lavalink listens on inside the container
+const LAVALINK_PORT: u16 = 2333;
+
+/// Line lavalink logs once it accepts connections
+const READY_MESSAGE: &str = "Lavalink is ready to accept connections.";
+
+/// Options of a lavalink container
+#[derive(Clone, Debug)]
+pub struct LavalinkContainerOptions {
+    /// Name the node options of the container get
+    pub name: String,
+    pub image: String,
+    pub tag: String,
+    pub password: String,
+    /// Full application.yml to use, one listening on port 2333 with http and local sources is generated if none
+    pub config: Option<String>,
+}
+
+impl Default for LavalinkContainerOptions {
+    fn default() -> Self {
+        Self {
+            name: String::from("Testcontainer"),
+            image: String::from("ghcr.io/lavalink-devs/lavalink"),
+            tag: String::from("4"),
+            password: String::from("youshallnotpass"),
+            config: None,
+        }
+    }
+}
+
+impl LavalinkContainerOptions {
+    /// Generates the application.yml of the container
+    fn application_yml(&self) -> String {
+        if let Some(config) = &self.config {
+            return config.clone();
+        }
+
+        format!(
+            "server:\n  port: {LAVALINK_PORT}\n  address: 0.0.0.0\nlavalink:\n  server:\n    password: \"{}\"\n    sources:\n      http: true\n      local: true\n",
+            self.password.replace('"', "\\\"")
+        )
+    }
+}
+
+/// Lavalink running in a docker container for end-to-end tests, removed once dropped
+pub struct LavalinkContainer {
+    /// Node options that connect to this container
+    pub options: NodeOptions,
+    container: ContainerAsync<GenericImage>,
+}
+
+impl LavalinkContainer {
+    /// Starts a lavalink container, then waits until it accepts connections
+    pub async fn start(options: LavalinkContainerOptions) -> Result<Self, AnchorageError> {
+        let container = GenericImage::new(options.image.as_str(), options.tag.as_str())
+            .with_exposed_port(LAVALINK_PORT.tcp())
+            .with_wait_for(WaitFor::message_on_stdout(READY_MESSAGE))
+            .with_copy_to(
+                "/opt/Lavalink/application.yml",
+                options.application_yml().into_bytes(),
+            )
+            .start()
+            .await?;
+
+        let host = container.get_host().await?.to_string();
+        let port = container.get_host_port_ipv4(LAVALINK_PORT.tcp()).await?;
+
+        let options = NodeOptions::new(options.name, host, port as u32, options.password)?;
+
+        Ok(Self { options, container })
+    }
+
+    /// Id of the docker container
+    pub fn id(&self) -> &str {
+        self.container.id()
+    }
+}
@@ -0,0 +1,284 @@
+//! Lavalink nodes served from memory, so players run without docker or the network
+#![allow(dead_code)]
+
+use anchorage::Anchorage;
+use anchorage::model::anchorage::{ConnectionOptions, NodeOptions, Options};
+use anchorage::model::error::LavalinkRestError;
+use anchorage::model::id::{ChannelId, GuildId, UserId};
+use anchorage::model::player::EventType;
+use anchorage::node::client::Node;
+use anchorage::node::transport::{HttpRequest, HttpResponse, HttpTransport, WsIo, WsTransport};
+use anchorage::player::events::EventReceiver;
+use futures::future::BoxFuture;
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{Error as IoError, ErrorKind};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::DuplexStream;
+use tokio::time::{sleep, timeout};
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::{Bytes, Message};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+pub const USER_ID: u64 = 424137718961012737;
+
+/// Longest time a test waits for something lavalink or Anchorage does in the background
+const WAIT: Duration = Duration::from_secs(5);
+
+/// Lavalink nodes reached by their host, keeping their players in memory
+#[derive(Default)]
+pub struct FakeLavalink {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Players of every node, mapped by host and guild id
+    players: HashMap<(String, String), Value>,
+    /// Nodes that accept websockets, their websockets close once the token is cancelled
+    up: HashMap<String, CancellationToken>,
+}
+
+impl FakeLavalink {
+    /// Accepts websockets on a host
+    pub fn add_node(&self, host: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .up
+            .insert(host.to_string(), CancellationToken::new());
+    }
+
+    /// Drops the websockets of a node without a close frame and refuses new ones, the way a crashed node looks
+    pub fn crash(&self, host: &str) {
+        if let Some(closed) = self.state.lock().unwrap().up.remove(host) {
+            closed.cancel();
+        }
+    }
+
+    /// Player a node has for a guild
+    pub fn player(&self, host: &str, guild_id: u64) -> Option<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .players
+            .get(&(host.to_string(), guild_id.to_string()))
+            .cloned()
+    }
+
+    /// Answers a REST request the way lavalink would, for the endpoints players use
+    fn respond(&self, request: HttpRequest) -> HttpResponse {
+        let url = Url::parse(&request.url).expect("requests have valid urls");
+        let host = url.host_str().unwrap_or_default().to_string();
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        let body: Option<Value> = request
+            .body
+            .as_deref()
+            .map(|body| serde_json::from_str(body).expect("request bodies are json"));
+
+        let mut state = self.state.lock().unwrap();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("PATCH", ["v4", "sessions", _]) => reply(StatusCode::OK, body.unwrap_or_default()),
+            ("GET", ["v4", "sessions", _, "players", guild_id]) => {
+                match state.players.get(&(host, guild_id.to_string())) {
+                    Some(player) => reply(StatusCode::OK, player.clone()),
+                    None => reply(
+                        StatusCode::NOT_FOUND,
+                        json!({ "message": "Player not found" }),
+                    ),
+                }
+            }
+            ("PATCH", ["v4", "sessions", _, "players", guild_id]) => {
+                let player = state
+                    .players
+                    .entry((host, guild_id.to_string()))
+                    .or_insert_with(|| new_player(guild_id));
+
+                let update = body.as_ref().and_then(Value::as_object);
+
+                for (key, value) in update.into_iter().flatten() {
+                    // tracks never play here, so only what a player keeps is stored
+                    if matches!(key.as_str(), "volume" | "paused" | "filters" | "voice") {
+                        player[key.as_str()] = value.clone();
+                    }
+                }
+
+                reply(StatusCode::OK, player.clone())
+            }
+            ("DELETE", ["v4", "sessions", _, "players", guild_id]) => {
+                state.players.remove(&(host, guild_id.to_string()));
+
+                HttpResponse {
+                    status: StatusCode::NO_CONTENT,
+                    body: Bytes::new(),
+                }
+            }
+            _ => reply(StatusCode::NOT_FOUND, json!({ "message": "Not found" })),
+        }
+    }
+}
+
+impl HttpTransport for FakeLavalink {
+    fn execute(
+        &self,
+        request: HttpRequest,
+    ) -> BoxFuture<'_, Result<HttpResponse, LavalinkRestError>> {
+        let response = self.respond(request);
+
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+impl WsTransport for FakeLavalink {
+    fn connect<'a>(
+        &'a self,
+        request: &'a Request,
+    ) -> BoxFuture<'a, Result<Box<dyn WsIo>, IoError>> {
+        let host = request.uri().host().unwrap_or_default().to_string();
+        let closed = self.state.lock().unwrap().up.get(&host).cloned();
+
+        Box::pin(async move {
+            let Some(closed) = closed else {
+                return Err(IoError::new(ErrorKind::ConnectionRefused, "node is down"));
+            };
+
+            let (client, server) = tokio::io::duplex(64 * 1024);
+
+            tokio::spawn(serve(server, host, closed));
+
+            Ok(Box::new(client) as Box<dyn WsIo>)
+        })
+    }
+}
+
+/// Runs the websocket of a node, which only sends the ready
+async fn serve(stream: DuplexStream, host: String, closed: CancellationToken) {
+    let Ok(mut websocket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+
+    let ready = json!({ "op": "ready", "resumed": false, "sessionId": format!("session-{host}") });
+
+    if websocket
+        .send(Message::Text(ready.to_string().into()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            _ = closed.cancelled() => break,
+            message = websocket.next() => {
+                if !matches!(message, Some(Ok(_))) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn reply(status: StatusCode, body: Value) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: Bytes::from(body.to_string()),
+    }
+}
+
+/// Player lavalink creates on the first update of a guild
+fn new_player(guild_id: &str) -> Value {
+    json!({
+        "guildId": guild_id,
+        "track": null,
+        "volume": 100,
+        "paused": false,
+        "state": { "time": 0, "position": 0, "connected": true, "ping": 0 },
+        "voice": { "token": "", "endpoint": "", "sessionId": "", "channelId": "0" },
+        "filters": {},
+    })
+}
+
+/// Options of a node served by [`FakeLavalink`], named after its host
+pub fn node_options(name: &str) -> NodeOptions {
+    NodeOptions {
+        name: name.to_string(),
+        host: name.to_string(),
+        port: 2333,
+        auth: "youshallnotpass".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Voice data of a player, the way Discord would send it
+pub fn connection(guild_id: u64) -> ConnectionOptions {
+    ConnectionOptions {
+        channel_id: ChannelId::new(1),
+        endpoint: "rotterdam1234.discord.media:443".to_string(),
+        guild_id: GuildId::new(guild_id),
+        session_id: "voice-session".to_string(),
+        token: "voice-token".to_string(),
+        user_id: UserId::new(USER_ID),
+        self_deaf: true,
+        self_mute: false,
+    }
+}
+
+/// Starts Anchorage on fake nodes, once every node is ready
+pub async fn start(mut options: Options, names: &[&str]) -> (Anchorage, Arc<FakeLavalink>) {
+    let lavalink = Arc::new(FakeLavalink::default());
+
+    for name in names {
+        lavalink.add_node(name);
+    }
+
+    options.ws_transport = Some(lavalink.clone());
+
+    let anchorage = Anchorage::with_transport(options, lavalink.clone());
+
+    let results = anchorage
+        .start(USER_ID, names.iter().map(|name| node_options(name)))
+        .await
+        .unwrap();
+
+    assert!(results.values().all(Result::is_ok));
+
+    // players need the session the ready brings
+    for (_, node) in anchorage.nodes_snapshot().await {
+        timeout(WAIT, async {
+            while node.rest.get_session_id().await.is_err() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("node should get ready");
+    }
+
+    (anchorage, lavalink)
+}
+
+/// Node of Anchorage with this name
+pub async fn node(anchorage: &Anchorage, name: &str) -> Node {
+    anchorage
+        .nodes_snapshot()
+        .await
+        .into_iter()
+        .find_map(|(node_name, node)| (node_name == name).then_some(node))
+        .expect("node should exist")
+}
+
+/// Next event of a player, failing the test if none comes
+pub async fn next_event(events: &mut EventReceiver) -> EventType {
+    timeout(WAIT, events.recv())
+        .await
+        .expect("an event should come")
+        .expect("the player should still have subscribers")
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use anchorage::Anchorage;
+use anchorage::model::anchorage::{NodeFailurePolicy, Options};
+use anchorage::model::id::GuildId;
+use anchorage::model::player::{DestroyReason, EventType};
+use anchorage::player::events::EventReceiver;
+use common::FakeLavalink;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+const GUILD_ID: u64 = 423116740810244097;
+
+/// Creates a player on a node, then crashes the node, which gives up reconnecting right away
+async fn crash_with_player(
+    policy: NodeFailurePolicy,
+) -> (Anchorage, Arc<FakeLavalink>, EventReceiver) {
+    let options = Options {
+        reconnect_tries: Some(0),
+        node_failure_policy: Some(policy),
+        ..Default::default()
+    };
+
+    let (anchorage, lavalink) = common::start(options, &["alpha", "beta"]).await;
+
+    let alpha = common::node(&anchorage, "alpha").await;
+
+    let (_, events) = anchorage
+        .create_player(GUILD_ID, alpha, common::connection(GUILD_ID))
+        .await
+        .unwrap();
+
+    lavalink.crash("alpha");
+
+    (anchorage, lavalink, events)
+}
+
+#[tokio::test]
+async fn migrates_players_of_failed_nodes() {
+    let (anchorage, lavalink, mut events) =
+        crash_with_player(NodeFailurePolicy::MigratePlayers).await;
+
+    let event = common::next_event(&mut events).await;
+
+    assert!(matches!(
+        event,
+        EventType::Moved { from, to } if from == "alpha" && to == "beta"
+    ));
+
+    let node = anchorage.get_node_for_player(GUILD_ID).await.unwrap();
+
+    assert_eq!(node.name, "beta");
+    assert!(lavalink.player("beta", GUILD_ID).is_some());
+}
+
+#[tokio::test]
+async fn keeps_orphans_of_failed_nodes() {
+    let (anchorage, _, mut events) = crash_with_player(NodeFailurePolicy::KeepOrphans).await;
+
+    let orphans = timeout(Duration::from_secs(5), async {
+        loop {
+            let orphans = anchorage.orphaned_players().await;
+
+            if !orphans.is_empty() {
+                return orphans;
+            }
+
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the player should be orphaned");
+
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].node, "alpha");
+    assert_eq!(orphans[0].guild_id, GuildId::new(GUILD_ID));
+    assert!(orphans[0].voice.is_some());
+
+    anchorage.destroy_player(GUILD_ID).await.unwrap();
+
+    // the orphan was never destroyed by the failure itself
+    assert!(matches!(
+        common::next_event(&mut events).await,
+        EventType::Destroyed(DestroyReason::Requested)
+    ));
+    assert!(anchorage.orphaned_players().await.is_empty());
+}
+
+#[tokio::test]
+async fn destroys_players_of_failed_nodes() {
+    let (anchorage, _, mut events) = crash_with_player(NodeFailurePolicy::DestroyPlayers).await;
+
+    assert!(matches!(
+        common::next_event(&mut events).await,
+        EventType::Destroyed(DestroyReason::NodeFailed)
+    ));
+    assert!(anchorage.orphaned_players().await.is_empty());
+}
@@ -0,0 +1,99 @@
+#![cfg(all(feature = "testcontainers", feature = "reqwest"))]
+
+use anchorage::Anchorage;
+use anchorage::model::anchorage::{ConnectionOptions, Options};
+use anchorage::model::id::{ChannelId, GuildId, UserId};
+use anchorage::model::player::{DataType, DestroyReason, EventType};
+use anchorage::testing::{LavalinkContainer, LavalinkContainerOptions};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+/// Audio file lavalink loads through its http source
+const TRACK_URL: &str = "https://www.soundhelix.com/examples/mp3/SoundHelix-Song-1.mp3";
+
+const USER_ID: u64 = 424137718961012737;
+
+const GUILD_ID: u64 = 423116740810244097;
+
+fn options() -> Options {
+    Options {
+        reconnect_tries: Some(0),
+        ..Default::default()
+    }
+}
+
+/// Runs against lavalink in docker, enable the testcontainers feature and run it with `cargo test -- --ignored`
+#[tokio::test]
+#[ignore = "needs docker, and lavalink loads the track from soundhelix.com"]
+async fn connects_loads_plays_and_destroys() {
+    let container = LavalinkContainer::start(LavalinkContainerOptions::default())
+        .await
+        .expect("lavalink container should start");
+
+    let anchorage = Anchorage::new(options());
+
+    let results = anchorage
+        .start(USER_ID, [container.options.clone()])
+        .await
+        .unwrap();
+
+    assert!(results.values().all(Result::is_ok));
+
+    let node = anchorage.get_ideal_node().await.unwrap();
+
+    // the session id arrives with the ready message, after the websocket connected
+    timeout(Duration::from_secs(30), async {
+        while node.rest.get_session_id().await.is_err() {
+            sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("node should get ready");
+
+    let DataType::Track(track) = node.rest.resolve(TRACK_URL).await.unwrap() else {
+        panic!("{TRACK_URL} should load as a single track");
+    };
+
+    let connection = ConnectionOptions {
+        channel_id: ChannelId::new(564749582744027156),
+        endpoint: String::from("localhost"),
+        guild_id: GuildId::new(GUILD_ID),
+        session_id: String::from("session"),
+        token: String::from("token"),
+        user_id: UserId::new(USER_ID),
+        self_deaf: true,
+        self_mute: false,
+    };
+
+    let (player, mut events) = anchorage
+        .create_player(GUILD_ID, node, connection)
+        .await
+        .unwrap();
+
+    player.play_track(&track).await.unwrap();
+
+    let data = player.get_data().await.unwrap();
+
+    assert_eq!(
+        data.track.map(|playing| playing.encoded),
+        Some(track.encoded)
+    );
+
+    anchorage.destroy_player(GUILD_ID).await.unwrap();
+
+    assert!(anchorage.get_node_for_player(GUILD_ID).await.is_none());
+
+    let destroyed = timeout(Duration::from_secs(5), async {
+        while let Some(event) = events.recv().await {
+            if let EventType::Destroyed(reason) = event {
+                return Some(reason);
+            }
+        }
+
+        None
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(destroyed, Some(DestroyReason::Requested));
+}
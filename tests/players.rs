@@ -0,0 +1,85 @@
+mod common;
+
+use anchorage::model::anchorage::Options;
+use anchorage::model::error::LavalinkPlayerError;
+use anchorage::model::player::{LavalinkFilters, LowPass};
+use anchorage::preset::Preset;
+
+const GUILD_ID: u64 = 423116740810244097;
+
+#[tokio::test]
+async fn applies_presets() {
+    let (anchorage, _) = common::start(Options::default(), &["alpha"]).await;
+
+    let node = common::node(&anchorage, "alpha").await;
+
+    let (player, _events) = anchorage
+        .create_player(GUILD_ID, node, common::connection(GUILD_ID))
+        .await
+        .unwrap();
+
+    let preset = Preset {
+        filters: Some(LavalinkFilters {
+            low_pass: Some(LowPass {
+                smoothing: Some(20.0),
+            }),
+            ..Default::default()
+        }),
+        volume: Some(80),
+    };
+
+    anchorage
+        .presets()
+        .register("muffled", preset)
+        .await
+        .unwrap();
+
+    player.apply_preset("muffled").await.unwrap();
+
+    let data = player.get_data().await.unwrap();
+
+    assert_eq!(data.volume, 80);
+    assert_eq!(
+        data.filters
+            .low_pass
+            .and_then(|low_pass| low_pass.smoothing),
+        Some(20.0)
+    );
+
+    let missing = player.apply_preset("missing").await;
+
+    assert!(matches!(
+        missing,
+        Err(LavalinkPlayerError::PresetNotFound(name)) if name == "missing"
+    ));
+}
+
+#[tokio::test]
+async fn changes_an_existing_low_pass() {
+    let (anchorage, _) = common::start(Options::default(), &["alpha"]).await;
+
+    let node = common::node(&anchorage, "alpha").await;
+
+    let (player, _events) = anchorage
+        .create_player(GUILD_ID, node, common::connection(GUILD_ID))
+        .await
+        .unwrap();
+
+    player.set_lowpass(20.0).await.unwrap();
+    player.set_mono(true).await.unwrap();
+    player.set_lowpass(5.0).await.unwrap();
+
+    let filters = player.get_data().await.unwrap().filters;
+
+    assert_eq!(
+        filters.low_pass.and_then(|low_pass| low_pass.smoothing),
+        Some(5.0)
+    );
+    // the other filters stay as they were
+    assert!(filters.channel_mix.is_some());
+
+    assert!(matches!(
+        player.set_lowpass(1.0).await,
+        Err(LavalinkPlayerError::InvalidSmoothing(_))
+    ));
+}